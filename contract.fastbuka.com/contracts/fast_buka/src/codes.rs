@@ -0,0 +1,17 @@
+use crate::datatypes::FastBukaError;
+use soroban_sdk::{Bytes, Env};
+
+// Salted SHA-256 of a code, the commitment parties submit in place of the code itself
+pub fn hash_code(e: &Env, salt: &Bytes, code: u32) -> Bytes {
+    let mut preimage = salt.clone();
+    preimage.extend_from_array(&code.to_be_bytes());
+    e.crypto().sha256(&preimage).to_bytes().into()
+}
+
+// Whether `code` and `salt` open a hash committed by the code's owner. Codes must have
+// exactly `length` digits, the code length recorded on the order when the hash was committed
+pub fn commitment_matches(e: &Env, hash: &Option<Bytes>, salt: &Bytes, code: u32, length: u32) -> Result<bool, FastBukaError> {
+    let hash = hash.as_ref().ok_or(FastBukaError::InvalidConfirmationNumber)?;
+    let in_range = (10u64.pow(length - 1)..10u64.pow(length)).contains(&(code as u64));
+    Ok(in_range && hash_code(e, salt, code) == *hash)
+}
@@ -0,0 +1,215 @@
+use crate::datatypes::{
+    DataKey, Dispute, DisputeParty, DisputeResolution, EscrowConfig, FastBukaError, Order, PauseGroup, TokenConfig, DEFAULT_APPEAL_BOND_BPS, DEFAULT_APPEAL_WINDOW, DEFAULT_ASSIGNMENT_TIMEOUT, DEFAULT_CODE_LENGTH, DEFAULT_CODE_LOCKOUT,
+    DEFAULT_CONFIRMATION_WINDOW, DEFAULT_DISPUTE_RESPONSE_WINDOW, DEFAULT_MAX_CODE_ATTEMPTS, DEFAULT_PICKUP_TIMEOUT, DEFAULT_PROPOSAL_EXPIRY,
+    DEFAULT_REQUIRED_APPROVALS, DEFAULT_WAITING_TIMEOUT,
+};
+use soroban_sdk::{Address, Env, Vec};
+
+pub fn read_config(e: &Env) -> EscrowConfig {
+    e.storage()
+        .instance()
+        .get(&DataKey::Config)
+        .unwrap_or(EscrowConfig {
+            waiting_timeout: DEFAULT_WAITING_TIMEOUT,
+            pickup_timeout: DEFAULT_PICKUP_TIMEOUT,
+            confirmation_window: DEFAULT_CONFIRMATION_WINDOW,
+            assignment_timeout: DEFAULT_ASSIGNMENT_TIMEOUT,
+            max_code_attempts: DEFAULT_MAX_CODE_ATTEMPTS,
+            code_lockout: DEFAULT_CODE_LOCKOUT,
+            code_length: DEFAULT_CODE_LENGTH,
+            required_approvals: DEFAULT_REQUIRED_APPROVALS,
+            proposal_expiry: DEFAULT_PROPOSAL_EXPIRY,
+            dispute_response_window: DEFAULT_DISPUTE_RESPONSE_WINDOW,
+            appeal_window: DEFAULT_APPEAL_WINDOW,
+            appeal_bond_bps: DEFAULT_APPEAL_BOND_BPS,
+        })
+}
+
+pub fn write_config(e: &Env, config: &EscrowConfig) {
+    e.storage().instance().set(&DataKey::Config, config);
+}
+
+// Whether the customer confirmation window of a delivered order has closed
+pub fn dispute_window_closed(e: &Env, order: &Order) -> bool {
+    match order.delivered_at {
+        Some(delivered_at) => {
+            e.ledger().timestamp() >= delivered_at.saturating_add(read_config(e).confirmation_window)
+        }
+        None => false,
+    }
+}
+
+// Arbitrator approvals needed before a resolution of this order's dispute pays out.
+// Orders in a token that is no longer configured fall back to the stricter rule.
+pub fn required_approvals(e: &Env, order: &Order) -> u32 {
+    let required = read_config(e).required_approvals;
+    match read_token_config(e, &order.token) {
+        Some(TokenConfig { approval_threshold: Some(threshold), .. }) if order.amount > threshold => required,
+        Some(_) => 1,
+        None => required,
+    }
+}
+
+// Which side of an order an address is on, if any
+pub fn order_party(order: &Order, account: &Address) -> Option<DisputeParty> {
+    if *account == order.user {
+        Some(DisputeParty::Customer)
+    } else if *account == order.vendor {
+        Some(DisputeParty::Vendor)
+    } else if Some(account.clone()) == order.rider {
+        Some(DisputeParty::Rider)
+    } else {
+        None
+    }
+}
+
+// Whether resolutions of this order's dispute wait out an appeal window before paying out
+pub fn resolution_appealable(e: &Env, order: &Order) -> bool {
+    matches!(
+        read_token_config(e, &order.token),
+        Some(TokenConfig { appeal_threshold: Some(threshold), .. }) if order.amount > threshold
+    )
+}
+
+// The party found at fault can appeal; any party can appeal a custom split
+pub fn can_appeal(resolution: &DisputeResolution, party: &DisputeParty) -> bool {
+    match resolution {
+        DisputeResolution::CustomerFault => *party == DisputeParty::Customer,
+        DisputeResolution::VendorFault => *party == DisputeParty::Vendor,
+        DisputeResolution::RiderFault => *party == DisputeParty::Rider,
+        DisputeResolution::Custom(_) => true,
+    }
+}
+
+// Parties to an order, other than the initiator, who may respond to its dispute
+pub fn dispute_respondents(e: &Env, order: &Order, dispute: &Dispute) -> Vec<DisputeParty> {
+    let mut parties = Vec::from_array(e, [DisputeParty::Customer, DisputeParty::Vendor]);
+    if order.rider.is_some() {
+        parties.push_back(DisputeParty::Rider);
+    }
+    if let Some(index) = parties.first_index_of(&dispute.initiator_role) {
+        parties.remove(index);
+    }
+    parties
+}
+
+// Whether a dispute is still waiting on responses: its window is open and someone has yet to respond
+pub fn dispute_awaiting_responses(e: &Env, order: &Order, dispute: &Dispute) -> bool {
+    if e.ledger().timestamp() >= dispute.response_deadline {
+        return false;
+    }
+    dispute_respondents(e, order, dispute)
+        .iter()
+        .any(|party| !dispute.responses.iter().any(|response| response.party == party))
+}
+
+pub fn read_treasury(e: &Env) -> Option<Address> {
+    e.storage().instance().get(&DataKey::Treasury)
+}
+
+pub fn write_treasury(e: &Env, treasury: &Address) {
+    e.storage().instance().set(&DataKey::Treasury, treasury);
+}
+
+// Fee in basis points charged on orders paid with `token`, falling back to the default fee
+pub fn read_platform_fee(e: &Env, token: &Address) -> u32 {
+    e.storage()
+        .instance()
+        .get(&DataKey::TokenPlatformFee(token.clone()))
+        .unwrap_or_else(|| e.storage().instance().get(&DataKey::PlatformFee).unwrap_or(0))
+}
+
+pub fn write_platform_fee(e: &Env, token: Option<Address>, fee_bps: u32) {
+    match token {
+        Some(token) => e.storage().instance().set(&DataKey::TokenPlatformFee(token), &fee_bps),
+        None => e.storage().instance().set(&DataKey::PlatformFee, &fee_bps),
+    }
+}
+
+pub fn read_token_config(e: &Env, token: &Address) -> Option<TokenConfig> {
+    e.storage().instance().get(&DataKey::AcceptedToken(token.clone()))
+}
+
+pub fn read_accepted_tokens(e: &Env) -> Vec<Address> {
+    e.storage()
+        .instance()
+        .get(&DataKey::AcceptedTokens)
+        .unwrap_or_else(|| Vec::new(e))
+}
+
+pub fn write_token_config(e: &Env, token: &Address, config: &TokenConfig) {
+    let mut tokens = read_accepted_tokens(e);
+    if !tokens.contains(token) {
+        tokens.push_back(token.clone());
+        e.storage().instance().set(&DataKey::AcceptedTokens, &tokens);
+    }
+    e.storage().instance().set(&DataKey::AcceptedToken(token.clone()), config);
+}
+
+pub fn remove_token_config(e: &Env, token: &Address) {
+    let mut tokens = read_accepted_tokens(e);
+    if let Some(index) = tokens.first_index_of(token) {
+        tokens.remove(index);
+        e.storage().instance().set(&DataKey::AcceptedTokens, &tokens);
+    }
+    e.storage().instance().remove(&DataKey::AcceptedToken(token.clone()));
+}
+
+// Whether an assigned rider has let the acceptance window lapse
+pub fn assignment_timed_out(e: &Env, order: &Order) -> bool {
+    match order.assigned_at {
+        Some(assigned_at) => {
+            !order.assignment_accepted
+                && e.ledger().timestamp() >= assigned_at.saturating_add(read_config(e).assignment_timeout)
+        }
+        None => false,
+    }
+}
+
+pub fn is_paused(e: &Env, group: PauseGroup) -> bool {
+    let storage = e.storage().instance();
+    storage.get(&DataKey::Paused(PauseGroup::All)).unwrap_or(false)
+        || storage.get(&DataKey::Paused(group)).unwrap_or(false)
+}
+
+pub fn write_paused(e: &Env, group: PauseGroup, paused: bool) {
+    if paused {
+        e.storage().instance().set(&DataKey::Paused(group), &true);
+    } else {
+        e.storage().instance().remove(&DataKey::Paused(group));
+    }
+}
+
+pub fn require_not_paused(e: &Env, group: PauseGroup) -> Result<(), FastBukaError> {
+    if is_paused(e, group) {
+        return Err(FastBukaError::ContractPaused);
+    }
+    Ok(())
+}
+
+// Schema version of the stored data; 0 for deployments from before versioning
+pub fn read_version(e: &Env) -> u32 {
+    e.storage().instance().get(&DataKey::Version).unwrap_or(0)
+}
+
+pub fn write_version(e: &Env, version: u32) {
+    e.storage().instance().set(&DataKey::Version, &version);
+}
+
+// Flat admin list used before roles existed, kept in persistent storage; only read when migrating
+pub fn read_legacy_admins(e: &Env) -> Vec<Address> {
+    e.storage()
+        .persistent()
+        .get(&DataKey::Admin)
+        .unwrap_or_else(|| Vec::new(e))
+}
+
+pub fn remove_legacy_admins(e: &Env) {
+    e.storage().persistent().remove(&DataKey::Admin);
+}
+
+// Order counter as kept in persistent storage before version 2. `migrate` leaves it in place
+// since it bounds the ids that can still be stored under bare keys
+pub fn read_legacy_order_count(e: &Env) -> u128 {
+    e.storage().persistent().get(&DataKey::OrderCounter).unwrap_or(0)
+}
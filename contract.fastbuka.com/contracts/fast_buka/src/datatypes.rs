@@ -1,13 +1,52 @@
 use soroban_sdk::{
-    Address,
-    contracterror, contracttype, String
+    Address, Bytes, BytesN,
+    contracterror, contracttype, String, Vec
 };
 
 
+// Storage schema version written by this build; bump it when `migrate` gains a step
+pub(crate) const CONTRACT_VERSION: u32 = 2;
+
 pub(crate) const DAY_IN_LEDGERS: u32 = 17280;
 pub(crate) const INSTANCE_BUMP_AMOUNT: u32 = 7 * DAY_IN_LEDGERS;
 pub(crate) const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
 
+// Orders and their index entries stay live for a month after they are last touched
+pub(crate) const ORDER_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub(crate) const ORDER_LIFETIME_THRESHOLD: u32 = ORDER_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+// Registries and shared lists outlive any single order
+pub(crate) const PERSISTENT_BUMP_AMOUNT: u32 = 90 * DAY_IN_LEDGERS;
+pub(crate) const PERSISTENT_LIFETIME_THRESHOLD: u32 = PERSISTENT_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+// Default escrow deadlines, in seconds
+pub(crate) const DEFAULT_WAITING_TIMEOUT: u64 = 60 * 60;
+pub(crate) const DEFAULT_PICKUP_TIMEOUT: u64 = 2 * 60 * 60;
+pub(crate) const DEFAULT_CONFIRMATION_WINDOW: u64 = 24 * 60 * 60;
+pub(crate) const DEFAULT_ASSIGNMENT_TIMEOUT: u64 = 10 * 60;
+pub(crate) const DEFAULT_MAX_CODE_ATTEMPTS: u32 = 3;
+pub(crate) const DEFAULT_CODE_LOCKOUT: u64 = 15 * 60;
+pub(crate) const DEFAULT_PROPOSAL_EXPIRY: u64 = 3 * 24 * 60 * 60;
+pub(crate) const DEFAULT_DISPUTE_RESPONSE_WINDOW: u64 = 24 * 60 * 60;
+pub(crate) const DEFAULT_APPEAL_WINDOW: u64 = 2 * 24 * 60 * 60;
+
+// Bond posted to appeal a resolution, in basis points of the order amount
+pub(crate) const DEFAULT_APPEAL_BOND_BPS: u32 = 500;
+
+// Arbitrator approvals needed to resolve a dispute above a token's approval threshold
+pub(crate) const DEFAULT_REQUIRED_APPROVALS: u32 = 2;
+
+// Confirmation codes are between 4 and 8 digits long
+pub(crate) const DEFAULT_CODE_LENGTH: u32 = 6;
+pub(crate) const MIN_CODE_LENGTH: u32 = 4;
+pub(crate) const MAX_CODE_LENGTH: u32 = 8;
+
+// Platform fees are expressed in basis points of the order subtotal
+pub(crate) const MAX_FEE_BPS: u32 = 10_000;
+
+// Most orders returned by a single page query
+pub(crate) const MAX_PAGE_LIMIT: u32 = 50;
+
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
@@ -15,11 +54,30 @@ pub enum DataKey {
     Balance(Address),
     Allowance(AllowanceDataKey),
     Admin,
+    Role(Role),
+    PendingAdmin(Address),
+    Version,
+    Config,
+    Paused(PauseGroup),
+    Treasury,
+    PlatformFee,
+    TokenPlatformFee(Address),
+    AcceptedToken(Address),
+    AcceptedTokens,
     DisputedOrders,
     // User storage
     Customer(Address),
     CustomerRegId(Address),
     OrderCounter,
+    Order(u128),
+    Dispute(u128),
+    RejectionReason(u128),
+    ResolutionProposal(u128),
+
+    // order id indexes
+    OrderIndexLen(OrderIndex),
+    OrderIndexEntry(OrderIndex, u32),
+    OrderIndexPos(OrderIndex, u128),
 
     // vendor
     Vendor(Address),
@@ -51,15 +109,38 @@ pub enum FastBukaError {
     CalculationError = 16,
     RiderPaymentFailed = 17,
     DepositPaymentFailed = 18,
-    OrderAlreadyDelivered = 19,
     OrderNotCompleted = 20,
     OrderCompleted = 21,
-    OrderNotPickedUp = 22,
     InsufficientFundsInContract = 23,
     AlreadyAdmin = 24,
     CustomerPaymentFailed = 25,
     NotACustomer = 26,
     OrderAlreadyCompleted = 27,
+    OrderNotCancellable = 28,
+    DeadlineNotReached = 29,
+    InvalidConfig = 30,
+    DeadlinePassed = 31,
+    TreasuryPaymentFailed = 32,
+    UnsupportedToken = 33,
+    VendorNotRegistered = 34,
+    VendorNotApproved = 35,
+    AlreadyRegistered = 36,
+    RiderNotRegistered = 37,
+    RiderNotApproved = 38,
+    RiderSuspended = 39,
+    RiderOffline = 40,
+    RiderNotAssigned = 41,
+    RiderAlreadyAssigned = 42,
+    CodeAttemptsExceeded = 43,
+    ContractPaused = 44,
+    MissingRole = 45,
+    LastSuperAdmin = 46,
+    ProposalNotFound = 47,
+    DuplicateSubmission = 48,
+    InvalidSplit = 49,
+    NotAppealable = 50,
+    InvalidTimeRange = 51,
+    ProposalPending = 52,
 }
 
 // Status Enum
@@ -75,14 +156,248 @@ pub enum OrderStatus {
     Cancelled = 5,
     Disputed = 6,
     Resolved = 7,
+    Expired = 8,
 }
 
 #[contracttype]
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum DisputeResolution {
     pub CustomerFault,
     pub VendorFault,
     pub RiderFault,
+    // Explicit shares of the escrowed amount, e.g. a partial refund for a missing item
+    pub Custom(DisputeSplit),
+}
+
+// Amounts paid out by a custom resolution; they must add up to the order amount
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DisputeSplit {
+    pub customer_amount: i128,
+    pub vendor_amount: i128,
+    pub rider_amount: i128,
+    pub platform_amount: i128,
+}
+
+// Party to an order that can take part in a dispute
+#[contracttype]
+#[derive(Clone, PartialEq, Debug)]
+#[repr(u32)]
+pub enum DisputeParty {
+    Customer = 0,
+    Vendor = 1,
+    Rider = 2,
+}
+
+// Supporting material kept off-chain; the hash pins down its content
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Evidence {
+    pub hash: BytesN<32>,
+    pub uri: String,
+}
+
+// Statement from a party other than the one who raised the dispute
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DisputeResponse {
+    pub party: DisputeParty,
+    pub responder: Address,
+    pub statement: String,
+    pub evidence: Vec<Evidence>,
+    pub responded_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum DisputeOutcome {
+    Pending,
+    // Decided but not paid out until the appeal window closes
+    Provisional(DisputeResolution),
+    // Appealed; payout is frozen until an appeal arbitrator decides
+    Appealed(DisputeResolution),
+    Resolved(DisputeResolution),
+}
+
+// On-chain record of a dispute, kept after it is resolved
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dispute {
+    pub order_id: u128,
+    pub initiator: Address,
+    pub initiator_role: DisputeParty,
+    pub reason: String,
+    pub evidence: Vec<Evidence>,
+    pub raised_at: u64,
+    pub response_deadline: u64,
+    pub responses: Vec<DisputeResponse>,
+    pub resolution: DisputeOutcome,
+    pub resolver: Option<Address>,
+    pub approvers: Vec<Address>,
+    pub resolved_at: Option<u64>,
+    pub appeal_deadline: Option<u64>,
+    pub appellant: Option<Address>,
+    pub appeal_bond: i128,
+}
+
+// Lists of order ids kept up to date as orders change
+#[contracttype]
+#[derive(Clone)]
+pub enum OrderIndex {
+    Customer(Address),
+    Vendor(Address),
+    Rider(Address),
+    Status(OrderStatus),
+    VendorStatus(Address, OrderStatus),
+}
+
+// Privileges that can be granted to accounts independently
+#[contracttype]
+#[derive(Clone, PartialEq, Debug)]
+#[repr(u32)]
+pub enum Role {
+    // Manages roles, escrow config and contract upgrades
+    SuperAdmin = 0,
+    // Resolves disputes
+    Arbitrator = 1,
+    // Approves and suspends vendors and riders
+    OnboardingManager = 2,
+    // Manages the treasury, platform fees and accepted tokens
+    TreasuryManager = 3,
+    // Pauses and unpauses operations
+    Pauser = 4,
+    // Decides appeals; cannot decide an appeal against their own resolution
+    AppealArbitrator = 5,
+    // Assigns riders to any order and replaces riders who have not picked up yet
+    Dispatcher = 6,
+}
+
+// Operations that can be paused independently; `All` pauses every group at once.
+// Refunds to customers are never paused.
+#[contracttype]
+#[derive(Clone, PartialEq, Debug)]
+#[repr(u32)]
+pub enum PauseGroup {
+    All = 0,
+    // Placing orders
+    NewOrders = 1,
+    // Everything from the vendor readying an order to the rider delivering it
+    Pickups = 2,
+    // Paying out completed orders
+    Settlements = 3,
+    // Raising, answering, resolving and appealing disputes
+    DisputeResolution = 4,
+    // Vendor and rider registration
+    Onboarding = 5,
+}
+
+// Admin-adjustable escrow settings
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct EscrowConfig {
+    // Seconds a vendor has to accept an order before it can be expired
+    pub waiting_timeout: u64,
+    // Seconds a ready order can wait for a rider before it can be expired
+    pub pickup_timeout: u64,
+    // Seconds a customer has to confirm or dispute a delivery before it can be finalized
+    pub confirmation_window: u64,
+    // Seconds an assigned rider has to accept before the order can be reassigned
+    pub assignment_timeout: u64,
+    // Wrong confirmation codes allowed before the order is locked
+    pub max_code_attempts: u32,
+    // Seconds an order stays locked after too many wrong codes
+    pub code_lockout: u64,
+    // Number of digits pickup and delivery codes must have. Codes are checked against the
+    // length in force when they were committed, so changing it only affects later commitments
+    pub code_length: u32,
+    // Arbitrator approvals needed to resolve a dispute above the token's approval threshold.
+    // Cannot exceed the number of arbitrators when set; revoking arbitrators afterwards can leave
+    // such disputes waiting until enough are granted again
+    pub required_approvals: u32,
+    // Seconds a resolution proposal stays open for approvals
+    pub proposal_expiry: u64,
+    // Seconds the other parties to a dispute have to respond before it can be resolved
+    pub dispute_response_window: u64,
+    // Seconds after resolving an appealable dispute during which the losing party can appeal
+    pub appeal_window: u64,
+    // Appeal bond in basis points of the order amount
+    pub appeal_bond_bps: u32,
+}
+
+// Payment token accepted for orders
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenConfig {
+    pub min_amount: i128,
+    pub max_amount: i128,
+    pub enabled: bool,
+    // Disputed orders above this amount need `required_approvals` arbitrators; None to never require them
+    pub approval_threshold: Option<i128>,
+    // Resolutions of disputed orders above this amount can be appealed; None to never allow appeals.
+    // Setting it needs a treasury and an appeal arbitrator who won't have approved the resolution,
+    // which the deployer alone, holding every role, is not. Revoking appeal arbitrators afterwards
+    // can leave appealed payouts waiting until one is granted again
+    pub appeal_threshold: Option<i128>,
+}
+
+// Onboarding state of a vendor
+#[contracttype]
+#[derive(Clone, PartialEq, Debug)]
+#[repr(u32)]
+pub enum VendorStatus {
+    Pending = 0,
+    Approved = 1,
+    Suspended = 2,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct VendorProfile {
+    pub name: String,
+    pub payout_address: Address,
+    pub metadata_uri: String,
+    pub status: VendorStatus,
+    pub registered_at: u64,
+}
+
+// Onboarding state of a rider
+#[contracttype]
+#[derive(Clone, PartialEq, Debug)]
+#[repr(u32)]
+pub enum RiderStatus {
+    Pending = 0,
+    Approved = 1,
+    Suspended = 2,
+}
+
+#[contracttype]
+#[derive(Clone, PartialEq, Debug)]
+#[repr(u32)]
+pub enum VehicleType {
+    Bicycle = 0,
+    Motorcycle = 1,
+    Car = 2,
+    OnFoot = 3,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RiderProfile {
+    pub vehicle_type: VehicleType,
+    pub metadata_uri: String,
+    pub status: RiderStatus,
+    pub online: bool,
+    pub registered_at: u64,
+}
+
+// Reason supplied by a vendor when declining an order
+#[contracttype]
+#[derive(Clone, PartialEq, Debug)]
+pub enum RejectionReason {
+    OutOfStock,
+    Closed,
+    TooBusy,
+    Other(String),
 }
 
 // Event Structures
@@ -110,6 +425,14 @@ pub struct ConfirmationGeneratedEvent {
     pub vendor: Address,
 }
 
+#[contracttype]
+#[derive(Clone)]
+pub struct RiderAssignedEvent {
+    pub order_id: u128,
+    pub rider: Address,
+    pub assigned_by: Address,
+}
+
 #[contracttype]
 #[derive(Clone)]
 pub struct OrderPickedUpEvent {
@@ -118,6 +441,56 @@ pub struct OrderPickedUpEvent {
 }
 
 
+#[contracttype]
+#[derive(Clone)]
+pub struct OrderCancelledEvent {
+    pub order_id: u128,
+    pub user: Address,
+    pub amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct OrderRejectedEvent {
+    pub order_id: u128,
+    pub vendor: Address,
+    pub reason: RejectionReason,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct OrderExpiredEvent {
+    pub order_id: u128,
+    pub user: Address,
+    pub amount: i128,
+    pub previous_status: OrderStatus,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct OrderSettledEvent {
+    pub order_id: u128,
+    pub vendor_amount: i128,
+    pub rider_amount: i128,
+    pub platform_fee: i128,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct VendorStatusUpdatedEvent {
+    pub vendor: Address,
+    pub status: VendorStatus,
+    pub admin: Address,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct RiderStatusUpdatedEvent {
+    pub rider: Address,
+    pub status: RiderStatus,
+    pub admin: Address,
+}
+
 #[contracttype]
 #[derive(Clone)]
 pub struct DisputeEvent {
@@ -126,18 +499,69 @@ pub struct DisputeEvent {
     pub reason: String,
 }
 
+// Resolution of a high-value dispute waiting for enough arbitrator approvals
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResolutionProposal {
+    pub order_id: u128,
+    pub resolution: DisputeResolution,
+    pub proposer: Address,
+    pub approvals: Vec<Address>,
+    pub required_approvals: u32,
+    pub created_at: u64,
+    pub expires_at: u64,
+}
+
 #[contracttype]
 #[derive(Clone)]
 pub struct DisputeResolvedEvent {
     pub order_id: u128,
     pub resolution: DisputeResolution,
     pub admin: Address,
+    pub platform_fee: i128,
+}
+
+// One page of an order query; `next_cursor` is None once the end is reached
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct OrderPage {
+    pub orders: Vec<Order>,
+    pub next_cursor: Option<u32>,
 }
 
 // Main Order Structure
 #[contracttype]
 #[derive(Clone, Debug)]
 pub struct Order {
+    pub id: u128,
+    pub user: Address,
+    pub token: Address,
+    pub vendor: Address,
+    pub amount: i128,
+    pub rider_fee: i128,
+    pub platform_fee: i128,
+    pub status: OrderStatus,
+    pub rider: Option<Address>,
+    pub assigned_rider: Option<Address>,
+    pub assigned_at: Option<u64>,
+    pub assignment_accepted: bool,
+    pub created_at: u64,
+    pub ready_at: Option<u64>,
+    pub delivered_at: Option<u64>,
+    pub pickup_code_hash: Option<Bytes>,
+    pub delivery_code_hash: Option<Bytes>,
+    pub pickup_code_length: u32,
+    pub delivery_code_length: u32,
+    pub failed_delivery_attempts: u32,
+    pub delivery_locked_until: u64,
+    pub failed_pickup_attempts: u32,
+    pub pickup_locked_until: u64,
+}
+
+// Order as stored under its bare id before `DataKey::Order`. Only read to migrate those entries
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct LegacyOrder {
     pub id: u128,
     pub user: Address,
     pub token: Address,
@@ -0,0 +1,152 @@
+use crate::{
+    config::{read_platform_fee, read_treasury, require_not_paused},
+    datatypes::{DisputeSplit, FastBukaError, Order, OrderSettledEvent, OrderStatus, PauseGroup, MAX_FEE_BPS},
+    index::set_order_status,
+    storage::write_order,
+    registry::vendor_payout_address,
+};
+use soroban_sdk::{symbol_short, token::Client as TokenClient, Address, Env};
+
+
+// Return the full escrowed amount of an order to the customer who paid it
+pub fn refund_customer(env: &Env, order: &Order) -> Result<(), FastBukaError> {
+    let token_client = TokenClient::new(env, &order.token);
+
+    let contract_balance = token_client.balance(&env.current_contract_address());
+    if order.amount > contract_balance {
+        return Err(FastBukaError::InsufficientFundsInContract);
+    }
+
+    let transfer_result = token_client.try_transfer(
+        &env.current_contract_address(),
+        &order.user,
+        &order.amount
+    );
+    if transfer_result.is_err() {
+        return Err(FastBukaError::CustomerPaymentFailed);
+    }
+
+    Ok(())
+}
+
+
+// Pay the vendor and rider their shares of a delivered order and mark it completed
+pub fn settle_order(env: &Env, order: &mut Order) -> Result<(), FastBukaError> {
+    require_not_paused(env, PauseGroup::Settlements)?;
+
+    let token_client = TokenClient::new(env, &order.token);
+
+    let contract_balance = token_client.balance(&env.current_contract_address());
+    if order.amount > contract_balance {
+        return Err(FastBukaError::InsufficientFundsInContract);
+    }
+
+    // Rider gets their fee, the platform its commission and vendor the rest
+    let rider_amount = order.rider_fee;
+    let vendor_amount = order
+        .amount
+        .checked_sub(rider_amount)
+        .and_then(|amount| amount.checked_sub(order.platform_fee))
+        .ok_or(FastBukaError::CalculationError)?;
+
+    let transfer_result = token_client.try_transfer(
+        &env.current_contract_address(),
+        &vendor_payout_address(env, &order.vendor),
+        &vendor_amount
+    );
+    if transfer_result.is_err() {
+        return Err(FastBukaError::VendorPaymentFailed);
+    }
+
+    let rider_address = order.rider.as_ref().ok_or(FastBukaError::OrderNotFound)?;
+    let transfer_result = token_client.try_transfer(
+        &env.current_contract_address(),
+        rider_address,
+        &rider_amount
+    );
+    if transfer_result.is_err() {
+        return Err(FastBukaError::RiderPaymentFailed);
+    }
+
+    pay_platform_fee(env, order)?;
+
+    set_order_status(env, order, OrderStatus::Completed);
+    write_order(env, order);
+
+    env.events().publish(
+        (symbol_short!("order"), symbol_short!("settled")),
+        OrderSettledEvent {
+            order_id: order.id,
+            vendor_amount,
+            rider_amount,
+            platform_fee: order.platform_fee,
+        }
+    );
+
+    Ok(())
+}
+
+
+// Commission owed to the platform on an order, taken from the subtotal after the rider fee
+pub fn calculate_platform_fee(env: &Env, token: &Address, total_amount: i128, rider_fee: i128) -> Result<i128, FastBukaError> {
+    let fee_bps = read_platform_fee(env, token);
+    if fee_bps == 0 {
+        return Ok(0);
+    }
+
+    total_amount
+        .checked_sub(rider_fee)
+        .and_then(|subtotal| subtotal.checked_mul(fee_bps as i128))
+        .map(|fee| fee / MAX_FEE_BPS as i128)
+        .ok_or(FastBukaError::CalculationError)
+}
+
+
+// Send the platform fee recorded on an order to the treasury
+pub fn pay_platform_fee(env: &Env, order: &Order) -> Result<(), FastBukaError> {
+    pay_treasury(env, order, order.platform_fee)
+}
+
+
+// Send part of an order's escrow to the treasury
+pub fn pay_treasury(env: &Env, order: &Order, amount: i128) -> Result<(), FastBukaError> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let treasury = read_treasury(env).ok_or(FastBukaError::InvalidConfig)?;
+    let token_client = TokenClient::new(env, &order.token);
+    let transfer_result = token_client.try_transfer(
+        &env.current_contract_address(),
+        &treasury,
+        &amount
+    );
+    if transfer_result.is_err() {
+        return Err(FastBukaError::TreasuryPaymentFailed);
+    }
+
+    Ok(())
+}
+
+
+// A custom dispute split has to pay out exactly the escrowed amount, and only to parties on the order
+pub fn check_split(order: &Order, split: &DisputeSplit) -> Result<(), FastBukaError> {
+    let shares = [split.customer_amount, split.vendor_amount, split.rider_amount, split.platform_amount];
+    if shares.iter().any(|share| *share < 0) {
+        return Err(FastBukaError::InvalidSplit);
+    }
+
+    if split.rider_amount > 0 && order.rider.is_none() {
+        return Err(FastBukaError::InvalidSplit);
+    }
+
+    let total = shares
+        .iter()
+        .try_fold(0i128, |total, share| total.checked_add(*share))
+        .ok_or(FastBukaError::InvalidSplit)?;
+    if total != order.amount {
+        return Err(FastBukaError::InvalidSplit);
+    }
+
+    Ok(())
+}
@@ -1,9 +1,25 @@
 use crate::{
-    datatypes::{DataKey, FastBukaError, Order, DisputeResolvedEvent, OrderStatus, DisputeResolution},
-    interface::AdminOperations,
+    config::{
+        dispute_awaiting_responses, is_paused, resolution_appealable, read_accepted_tokens, read_legacy_admins, read_legacy_order_count, read_config, read_platform_fee, read_token_config, read_treasury,
+        read_version, remove_token_config, required_approvals, require_not_paused, remove_legacy_admins, write_config, write_platform_fee, write_token_config,
+        write_paused, write_treasury, write_version,
+    },
+    datatypes::{DataKey, Dispute, DisputeOutcome, FastBukaError, Order, OrderIndex, DisputeResolvedEvent, OrderStatus, PauseGroup, DisputeResolution, EscrowConfig, TokenConfig, VendorStatus, VendorStatusUpdatedEvent, RiderStatus, RiderStatusUpdatedEvent, ResolutionProposal, Role, CONTRACT_VERSION, MAX_CODE_LENGTH, MAX_FEE_BPS, MIN_CODE_LENGTH},
+    escrow::{check_split, pay_treasury},
+    index::{add_to_index, index_new_order, set_order_status},
+    interface::{AdminOperations, OrderManagement},
+    registry::{read_rider, read_vendor, vendor_payout_address, write_rider, write_vendor},
+    roles::{
+        all_roles, appeals_decidable, grant_role, has_role, is_last_super_admin, read_pending_admin, read_role_members, remove_pending_admin,
+        require_role, revoke_role, write_pending_admin,
+    },
+    storage::{
+        migrate_legacy_order, read_dispute, read_disputed_orders, read_order, read_resolution_proposal, remove_resolution_proposal,
+        write_dispute, write_disputed_orders, write_order, write_resolution_proposal,
+    },
     FastBukaContract, FastBukaContractArgs, FastBukaContractClient
 };
-use soroban_sdk::{contractimpl, Address, Env, Vec, token::Client as TokenClient, symbol_short};
+use soroban_sdk::{contractimpl, Address, BytesN, Env, Vec, token::Client as TokenClient, symbol_short};
 
 
 #[contractimpl]
@@ -11,82 +27,153 @@ impl AdminOperations for FastBukaContract {
 
     // Getter function for admin
     fn get_admins(env: Env) -> Vec<Address> {
-        env.storage().persistent()
-            .get(&DataKey::Admin)
-            .unwrap_or_else(|| Vec::new(&env))
+        read_role_members(&env, Role::SuperAdmin)
     }
 
-    fn add_admin(env: Env, caller: Address, new_admin: Address) -> Result<(), FastBukaError> {
+    // First half of the admin handoff; the proposed account only becomes a super admin
+    // once it accepts, so a mistyped address never gains or loses anything
+    fn propose_admin(env: Env, caller: Address, new_admin: Address) -> Result<(), FastBukaError> {
         // Verify the caller is an existing admin
         caller.require_auth();
+        require_role(&env, &caller, Role::SuperAdmin)?;
 
-        // Fetch the current list of admins from storage
-        let mut admins: Vec<Address> = env.storage().persistent()
-            .get(&DataKey::Admin)
-            .unwrap_or_else(|| Vec::new(&env));
+        Self::propose_super_admin(&env, caller, new_admin)
+    }
 
-        // Check if the caller is in the admin list
-        if !admins.contains(&caller) {
-            return Err(FastBukaError::NotAdmin);
-        }
 
-        // Add the new admin if not already in the list
-        if admins.contains(&new_admin) {
-            return Err(FastBukaError::AlreadyAdmin);
+    fn accept_admin(env: Env, new_admin: Address) -> Result<(), FastBukaError> {
+        // Only the proposed account can accept
+        new_admin.require_auth();
+
+        let proposer = read_pending_admin(&env, &new_admin).ok_or(FastBukaError::ProposalNotFound)?;
+        remove_pending_admin(&env, &new_admin);
+
+        // The proposer may have lost the role since proposing
+        require_role(&env, &proposer, Role::SuperAdmin)?;
+        grant_role(&env, &new_admin, Role::SuperAdmin);
+
+        // Publish admin event
+        env.events().publish(
+            (symbol_short!("admin"), symbol_short!("accepted")),
+            (new_admin, proposer)
+        );
+
+        Ok(())
+    }
+
+
+    fn cancel_admin_proposal(env: Env, caller: Address, new_admin: Address) -> Result<(), FastBukaError> {
+        // Verify the caller is an existing admin
+        caller.require_auth();
+        require_role(&env, &caller, Role::SuperAdmin)?;
+
+        if read_pending_admin(&env, &new_admin).is_none() {
+            return Err(FastBukaError::ProposalNotFound);
         }
+        remove_pending_admin(&env, &new_admin);
 
-        admins.push_back(new_admin);
-        env.storage().persistent().set(&DataKey::Admin, &admins);
         Ok(())
-    } 
+    }
+
+
+    fn get_pending_admin_proposer(env: Env, new_admin: Address) -> Option<Address> {
+        read_pending_admin(&env, &new_admin)
+    }
 
 
     fn remove_admin(env: Env, caller: Address, admin_to_remove: Address) -> Result<(), FastBukaError> {
         // Verify the caller is an existing admin
         caller.require_auth();
+        require_role(&env, &caller, Role::SuperAdmin)?;
 
-        // Fetch the current list of admins from storage
-        let mut admins: Vec<Address> = env.storage().persistent()
-            .get(&DataKey::Admin)
-            .unwrap_or_else(|| Vec::new(&env));
-
-        // Check if the caller is in the admin list
-        if !admins.contains(&caller) {
-            return Err(FastBukaError::NotAdmin);
+        // The contract must always keep at least one super admin
+        if is_last_super_admin(&env, &admin_to_remove) {
+            return Err(FastBukaError::LastSuperAdmin);
         }
 
         // Check if the admin to remove exists in the list
-        if !admins.contains(&admin_to_remove) {
+        if !has_role(&env, &admin_to_remove, Role::SuperAdmin) {
             return Err(FastBukaError::NotAdmin);
         }
 
-       // Find the index of the admin to remove
-        let remove_index = admins.iter()
-        .position(|admin| admin == admin_to_remove)
-        .ok_or(FastBukaError::NotAdmin)?;
+        // A removed admin keeps none of the roles they held
+        for role in all_roles(&env).iter() {
+            if revoke_role(&env, &admin_to_remove, role.clone()) {
+                // Publish role event
+                env.events().publish(
+                    (symbol_short!("role"), symbol_short!("revoked")),
+                    (role, admin_to_remove.clone(), caller.clone())
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+
+    fn grant_role(env: Env, admin: Address, account: Address, role: Role) -> Result<(), FastBukaError> {
+        // Verify admin authentication
+        admin.require_auth();
+        require_role(&env, &admin, Role::SuperAdmin)?;
+
+        // Super admin is only handed over through propose_admin/accept_admin
+        if role == Role::SuperAdmin {
+            return Self::propose_super_admin(&env, admin, account);
+        }
+
+        if grant_role(&env, &account, role.clone()) {
+            // Publish role event
+            env.events().publish(
+                (symbol_short!("role"), symbol_short!("granted")),
+                (role, account, admin)
+            );
+        }
+
+        Ok(())
+    }
+
+
+    fn revoke_role(env: Env, admin: Address, account: Address, role: Role) -> Result<(), FastBukaError> {
+        // Verify admin authentication
+        admin.require_auth();
+        require_role(&env, &admin, Role::SuperAdmin)?;
+
+        if role == Role::SuperAdmin && is_last_super_admin(&env, &account) {
+            return Err(FastBukaError::LastSuperAdmin);
+        }
 
-        // Remove the admin at the found index
-        admins.remove(remove_index.try_into().unwrap());
-        env.storage().persistent().set(&DataKey::Admin, &admins);
+        if revoke_role(&env, &account, role.clone()) {
+            // Publish role event
+            env.events().publish(
+                (symbol_short!("role"), symbol_short!("revoked")),
+                (role, account, admin)
+            );
+        }
 
         Ok(())
     }
 
 
+    fn has_role(env: Env, account: Address, role: Role) -> bool {
+        has_role(&env, &account, role)
+    }
+
+
+    fn get_role_members(env: Env, role: Role) -> Vec<Address> {
+        read_role_members(&env, role)
+    }
+
+
     // Admin operation to retrieve all disputed orders
     fn get_all_disputed_orders(env: Env) -> Vec<u128> {
         // Retrieve all disputed order IDs
-        let disputed_orders: Vec<u128> = env.storage().persistent()
-            .get(&DataKey::DisputedOrders)
-            .unwrap_or_else(|| Vec::new(&env));
+        let disputed_orders = read_disputed_orders(&env);
     
         // Map the order IDs to include reasons (or other metadata)
         let mut disputes_with_details = Vec::new(&env);
         for order_id in disputed_orders.iter() {
-            if let Some(_order) = env.storage().persistent().get::<u128, Order>(&order_id) {
-                disputes_with_details.push_back(
-                    order_id.clone(),
-                );
+            if let Some(_order) = read_order(&env, order_id) {
+                disputes_with_details.push_back(order_id);
             }
         }
     
@@ -94,46 +181,529 @@ impl AdminOperations for FastBukaContract {
     }
 
 
+    fn get_dispute(env: Env, order_id: u128) -> Result<Dispute, FastBukaError> {
+        read_dispute(&env, order_id).ok_or(FastBukaError::DisputeNotFound)
+    }
+
+
+
+    fn set_config(env: Env, admin: Address, config: EscrowConfig) -> Result<(), FastBukaError> {
+        // Verify admin authentication
+        admin.require_auth();
+        require_role(&env, &admin, Role::SuperAdmin)?;
+
+        // Deadlines of zero would let anyone expire or finalize an order immediately
+        if config.waiting_timeout == 0
+            || config.pickup_timeout == 0
+            || config.confirmation_window == 0
+            || config.assignment_timeout == 0
+            || config.max_code_attempts == 0
+        {
+            return Err(FastBukaError::InvalidConfig);
+        }
+
+        // More approvals than there are arbitrators could never be collected
+        if config.required_approvals > read_role_members(&env, Role::Arbitrator).len() {
+            return Err(FastBukaError::InvalidConfig);
+        }
+
+        if config.required_approvals == 0
+            || config.proposal_expiry == 0
+            || config.dispute_response_window == 0
+            || config.appeal_window == 0
+        {
+            return Err(FastBukaError::InvalidConfig);
+        }
+
+        if config.appeal_bond_bps > MAX_FEE_BPS {
+            return Err(FastBukaError::InvalidConfig);
+        }
+
+        if config.code_length < MIN_CODE_LENGTH || config.code_length > MAX_CODE_LENGTH {
+            return Err(FastBukaError::InvalidConfig);
+        }
+
+        write_config(&env, &config);
+        Ok(())
+    }
+
+
+    fn get_config(env: Env) -> EscrowConfig {
+        read_config(&env)
+    }
+
+
+    fn set_treasury(env: Env, admin: Address, treasury: Address) -> Result<(), FastBukaError> {
+        // Verify admin authentication
+        admin.require_auth();
+        require_role(&env, &admin, Role::TreasuryManager)?;
+
+        write_treasury(&env, &treasury);
+        Ok(())
+    }
+
+
+    fn get_treasury(env: Env) -> Option<Address> {
+        read_treasury(&env)
+    }
+
+
+    // Set the default platform fee, or the fee for a single token when one is given
+    fn set_platform_fee(env: Env, admin: Address, token: Option<Address>, fee_bps: u32) -> Result<(), FastBukaError> {
+        // Verify admin authentication
+        admin.require_auth();
+        require_role(&env, &admin, Role::TreasuryManager)?;
+
+        if fee_bps > MAX_FEE_BPS {
+            return Err(FastBukaError::InvalidConfig);
+        }
+
+        // Fees need somewhere to go before they can be charged
+        if fee_bps > 0 && read_treasury(&env).is_none() {
+            return Err(FastBukaError::InvalidConfig);
+        }
+
+        write_platform_fee(&env, token, fee_bps);
+        Ok(())
+    }
+
+
+    fn get_platform_fee(env: Env, token: Address) -> u32 {
+        read_platform_fee(&env, &token)
+    }
+
+
+    // Allow a payment token for new orders, or update its limits
+    fn add_accepted_token(env: Env, admin: Address, token: Address, config: TokenConfig) -> Result<(), FastBukaError> {
+        // Verify admin authentication
+        admin.require_auth();
+        require_role(&env, &admin, Role::TreasuryManager)?;
+
+        if config.min_amount <= 0 || config.max_amount < config.min_amount {
+            return Err(FastBukaError::InvalidConfig);
+        }
+
+        // Upheld appeals forfeit the bond to the treasury, so appeals need one to be set, and
+        // someone who didn't approve the resolution has to be able to decide them
+        if config.appeal_threshold.is_some() && (read_treasury(&env).is_none() || !appeals_decidable(&env)) {
+            return Err(FastBukaError::InvalidConfig);
+        }
+
+        write_token_config(&env, &token, &config);
+        Ok(())
+    }
+
+
+    fn remove_accepted_token(env: Env, admin: Address, token: Address) -> Result<(), FastBukaError> {
+        // Verify admin authentication
+        admin.require_auth();
+        require_role(&env, &admin, Role::TreasuryManager)?;
+
+        if read_token_config(&env, &token).is_none() {
+            return Err(FastBukaError::UnsupportedToken);
+        }
+
+        remove_token_config(&env, &token);
+        Ok(())
+    }
+
+
+    fn get_accepted_tokens(env: Env) -> Vec<Address> {
+        read_accepted_tokens(&env)
+    }
+
+
+    fn get_token_config(env: Env, token: Address) -> Option<TokenConfig> {
+        read_token_config(&env, &token)
+    }
+
+
+    fn approve_vendor(env: Env, admin: Address, vendor: Address) -> Result<(), FastBukaError> {
+        Self::set_vendor_status(&env, admin, vendor, VendorStatus::Approved)
+    }
+
+
+    fn suspend_vendor(env: Env, admin: Address, vendor: Address) -> Result<(), FastBukaError> {
+        Self::set_vendor_status(&env, admin, vendor, VendorStatus::Suspended)
+    }
+
+
+    fn approve_rider(env: Env, admin: Address, rider: Address) -> Result<(), FastBukaError> {
+        Self::set_rider_status(&env, admin, rider, RiderStatus::Approved)
+    }
+
+
+    fn suspend_rider(env: Env, admin: Address, rider: Address) -> Result<(), FastBukaError> {
+        Self::set_rider_status(&env, admin, rider, RiderStatus::Suspended)
+    }
+
 
     fn resolve_dispute(env: Env, order_id: u128, resolution: DisputeResolution, admin: Address) -> Result<(), FastBukaError> {
         // 1. Verify admin authentication
         admin.require_auth();
 
-        // 2. Check if admin is actually an admin
-        let admins = Self::get_admins(env.clone());
-        if !admins.contains(&admin) {
-            return Err(FastBukaError::NotAdmin);
-        }
+        // 2. Only arbitrators can resolve disputes
+        require_role(&env, &admin, Role::Arbitrator)?;
+
+        require_not_paused(&env, PauseGroup::DisputeResolution)?;
 
         // 3. Get the disputed order
-        let mut order: Order = env
-            .storage()
-            .persistent()
-            .get(&order_id)
-            .ok_or(FastBukaError::OrderNotFound)?;
+        let mut order: Order = read_order(&env, order_id).ok_or(FastBukaError::OrderNotFound)?;
 
         // 4. Verify order is in disputed status
         if order.status != OrderStatus::Disputed {
             return Err(FastBukaError::DisputeNotFound);
         }
 
+        // The other parties get their say first, unless they have all responded already
+        if let Some(dispute) = read_dispute(&env, order_id) {
+            if dispute.resolution != DisputeOutcome::Pending {
+                return Err(FastBukaError::AlreadyResolved);
+            }
+            if dispute_awaiting_responses(&env, &order, &dispute) {
+                return Err(FastBukaError::DeadlineNotReached);
+            }
+        }
+
+        // Custom splits must pay out exactly the escrowed amount
+        if let DisputeResolution::Custom(split) = &resolution {
+            check_split(&order, split)?;
+        }
+
+        // 5. High-value disputes only pay out once enough arbitrators approve
+        let required_approvals = required_approvals(&env, &order);
+        if required_approvals > 1 {
+            let now = env.ledger().timestamp();
+            if let Some(proposal) = read_resolution_proposal(&env, order_id) {
+                if now < proposal.expires_at {
+                    return Err(FastBukaError::ProposalPending);
+                }
+            }
+
+            let proposal = ResolutionProposal {
+                order_id,
+                resolution,
+                proposer: admin.clone(),
+                approvals: Vec::from_array(&env, [admin]),
+                required_approvals,
+                created_at: now,
+                expires_at: now.saturating_add(read_config(&env).proposal_expiry),
+            };
+            write_resolution_proposal(&env, &proposal);
+
+            // Publish proposal event
+            env.events().publish(
+                (symbol_short!("dispute"), symbol_short!("proposed")),
+                proposal
+            );
+            return Ok(());
+        }
+
+        // 6. Pay out the resolution
+        Self::execute_resolution(&env, &mut order, resolution, admin.clone(), Vec::from_array(&env, [admin]))
+    }
+
+
+    // Add an arbitrator's approval to a pending resolution, executing it once the threshold is met
+    fn approve_resolution(env: Env, order_id: u128, arbitrator: Address) -> Result<(), FastBukaError> {
+        // Verify arbitrator authentication
+        arbitrator.require_auth();
+        require_role(&env, &arbitrator, Role::Arbitrator)?;
+
+        require_not_paused(&env, PauseGroup::DisputeResolution)?;
+
+        let mut proposal = read_resolution_proposal(&env, order_id).ok_or(FastBukaError::ProposalNotFound)?;
+
+        // Stale proposals have to be proposed again through resolve_dispute
+        if env.ledger().timestamp() >= proposal.expires_at {
+            return Err(FastBukaError::DeadlinePassed);
+        }
+
+        if proposal.approvals.contains(&arbitrator) {
+            return Err(FastBukaError::DuplicateSubmission);
+        }
+
+        // Approvals only count while the approver is still an arbitrator
+        let mut approvals = Vec::new(&env);
+        for approver in proposal.approvals.iter() {
+            if has_role(&env, &approver, Role::Arbitrator) {
+                approvals.push_back(approver);
+            }
+        }
+        approvals.push_back(arbitrator.clone());
+        proposal.approvals = approvals;
+
+        // Publish approval event
+        env.events().publish(
+            (symbol_short!("dispute"), symbol_short!("approved")),
+            (order_id, arbitrator.clone(), proposal.approvals.len())
+        );
+
+        if proposal.approvals.len() < proposal.required_approvals {
+            write_resolution_proposal(&env, &proposal);
+            return Ok(());
+        }
+
+        let mut order: Order = read_order(&env, order_id).ok_or(FastBukaError::OrderNotFound)?;
+        if order.status != OrderStatus::Disputed {
+            return Err(FastBukaError::DisputeNotFound);
+        }
+
+        remove_resolution_proposal(&env, order_id);
+        Self::execute_resolution(&env, &mut order, proposal.resolution, arbitrator, proposal.approvals)
+    }
+
+
+    fn get_resolution_proposal(env: Env, order_id: u128) -> Option<ResolutionProposal> {
+        read_resolution_proposal(&env, order_id)
+    }
+
+
+    // Second-tier decision on an appealed resolution. Deciding the same resolution confirms it and
+    // forfeits the appeal bond to the treasury; anything else overturns it and refunds the bond.
+    fn decide_appeal(env: Env, order_id: u128, arbitrator: Address, resolution: DisputeResolution) -> Result<(), FastBukaError> {
+        // 1. Verify arbitrator authentication
+        arbitrator.require_auth();
+        require_role(&env, &arbitrator, Role::AppealArbitrator)?;
+
+        require_not_paused(&env, PauseGroup::DisputeResolution)?;
+
+        // 2. Get the appealed dispute
+        let mut order: Order = read_order(&env, order_id).ok_or(FastBukaError::OrderNotFound)?;
+        if order.status != OrderStatus::Disputed {
+            return Err(FastBukaError::DisputeNotFound);
+        }
+        let dispute = read_dispute(&env, order_id).ok_or(FastBukaError::DisputeNotFound)?;
+        let original = match dispute.resolution {
+            DisputeOutcome::Appealed(original) => original,
+            _ => return Err(FastBukaError::NotAppealable),
+        };
+
+        // 3. Appeals go to someone who did not approve the original resolution
+        if dispute.approvers.contains(&arbitrator) {
+            return Err(FastBukaError::UnauthorizedAccess);
+        }
+
+        if let DisputeResolution::Custom(split) = &resolution {
+            check_split(&order, split)?;
+        }
+
+        // 4. Settle the appeal bond
+        let appellant = dispute.appellant.ok_or(FastBukaError::NotAppealable)?;
+        let upheld = resolution == original;
+        if upheld {
+            pay_treasury(&env, &order, dispute.appeal_bond)?;
+        } else if dispute.appeal_bond > 0 {
+            let token_client = TokenClient::new(&env, &order.token);
+            let refund = token_client.try_transfer(
+                &env.current_contract_address(),
+                &appellant,
+                &dispute.appeal_bond
+            );
+            if refund.is_err() {
+                return Err(FastBukaError::DepositPaymentFailed);
+            }
+        }
+
+        // Publish appeal event
+        env.events().publish(
+            (symbol_short!("appeal"), if upheld { symbol_short!("confirmed") } else { symbol_short!("overturn") }),
+            (order_id, arbitrator.clone(), appellant, dispute.appeal_bond)
+        );
+
+        // 5. Pay out the final resolution
+        Self::pay_out_resolution(&env, &mut order, resolution, arbitrator)
+    }
+
+
+    // Halt a group of operations, or every group with `PauseGroup::All`. Refunds keep working.
+    fn set_paused(env: Env, admin: Address, group: PauseGroup, paused: bool) -> Result<(), FastBukaError> {
+        // Verify admin authentication
+        admin.require_auth();
+        require_role(&env, &admin, Role::Pauser)?;
+
+        write_paused(&env, group.clone(), paused);
+
+        // Publish pause event
+        env.events().publish(
+            (symbol_short!("pause"), group),
+            (paused, admin)
+        );
+
+        Ok(())
+    }
+
+
+    fn is_paused(env: Env, group: PauseGroup) -> bool {
+        is_paused(&env, group)
+    }
+
+
+    // Swap the contract code in place, keeping storage and escrowed funds.
+    // Call `migrate` afterwards to bring stored data up to the new version.
+    fn upgrade(env: Env, admin: Address, new_wasm_hash: BytesN<32>) -> Result<(), FastBukaError> {
+        // Verify admin authentication
+        admin.require_auth();
+        require_role(&env, &admin, Role::SuperAdmin)?;
+
+        env.deployer().update_current_contract_wasm(new_wasm_hash.clone());
+
+        // Publish upgrade event
+        env.events().publish(
+            (symbol_short!("contract"), symbol_short!("upgraded")),
+            (new_wasm_hash, read_version(&env), admin)
+        );
+
+        Ok(())
+    }
+
+
+    // Post-upgrade hook that runs the storage migrations between the stored and current version
+    fn migrate(env: Env, admin: Address) -> Result<u32, FastBukaError> {
+        // Verify admin authentication
+        admin.require_auth();
+
+        // Before version 2 admins were a flat list, which is what the caller has to be on
+        let from_version = read_version(&env);
+        let legacy_admins = read_legacy_admins(&env);
+        let is_legacy_admin = from_version < 2 && legacy_admins.contains(&admin);
+        if !is_legacy_admin && !has_role(&env, &admin, Role::SuperAdmin) {
+            return Err(FastBukaError::MissingRole);
+        }
+
+        if from_version >= CONTRACT_VERSION {
+            return Ok(from_version);
+        }
+
+        // Version-specific steps go here, oldest first, each guarded by `from_version`.
+        // Orders under bare u128 keys are moved separately in batches by `migrate_order_keys`.
 
-        // 5. Get token client
-        let token_client = TokenClient::new(&env, &order.token);
+        // 2: flat admin list replaced by roles; existing admins keep every privilege
+        if from_version < 2 {
+            for legacy_admin in legacy_admins.iter() {
+                for role in all_roles(&env).iter() {
+                    grant_role(&env, &legacy_admin, role);
+                }
+            }
+            remove_legacy_admins(&env);
+        }
+
+        // 2: order counter moved from persistent to instance storage
+        if from_version < 2 {
+            let count = Self::get_order_count(&env);
+            env.storage().instance().set(&DataKey::OrderCounter, &count);
+        }
+
+        write_version(&env, CONTRACT_VERSION);
+
+        // Publish migration event
+        env.events().publish(
+            (symbol_short!("contract"), symbol_short!("migrated")),
+            (from_version, CONTRACT_VERSION)
+        );
+
+        Ok(CONTRACT_VERSION)
+    }
+
+
+    fn get_version(env: Env) -> u32 {
+        read_version(&env)
+    }
+
+
+    // Moves orders `start_id..start_id + limit` stored under bare u128 keys to `DataKey::Order`.
+    // Only ids up to the pre-version 2 order count can be legacy orders. Safe to re-run;
+    // returns how many orders were moved.
+    fn migrate_order_keys(env: Env, admin: Address, start_id: u128, limit: u32) -> Result<u32, FastBukaError> {
+        // Verify admin authentication
+        admin.require_auth();
+        require_role(&env, &admin, Role::SuperAdmin)?;
+
+        let end_id = start_id
+            .saturating_add(limit as u128)
+            .min(read_legacy_order_count(&env).saturating_add(1));
+
+        let mut migrated = 0;
+        let mut order_id = start_id.max(1);
+        while order_id < end_id {
+            if let Some(order) = migrate_legacy_order(&env, order_id) {
+                // Orders from before the indexes existed are added to them as well
+                index_new_order(&env, &order);
+                if let Some(rider) = &order.rider {
+                    add_to_index(&env, &OrderIndex::Rider(rider.clone()), order_id);
+                }
+                migrated += 1;
+            } else if env.storage().persistent().has(&order_id) {
+                // The id was reused by a newer order, so the legacy one is left for manual recovery
+                env.events().publish(
+                    (symbol_short!("orders"), symbol_short!("conflict")),
+                    order_id
+                );
+            }
+            order_id += 1;
+        }
+
+        // Publish migration event
+        env.events().publish(
+            (symbol_short!("orders"), symbol_short!("migrated")),
+            (start_id, migrated)
+        );
+
+        Ok(migrated)
+    }
+}
+
+
+impl FastBukaContract {
+    // Resolves a dispute, holding the payout first if the resolution can be appealed
+    fn execute_resolution(env: &Env, order: &mut Order, resolution: DisputeResolution, admin: Address, approvers: Vec<Address>) -> Result<(), FastBukaError> {
+        if let Some(mut dispute) = read_dispute(env, order.id) {
+            // Everyone who approved the resolution is kept off its appeal
+            dispute.approvers = approvers;
+
+            // Larger orders hold the payout while the losing party can still appeal
+            if resolution_appealable(env, order) {
+                let now = env.ledger().timestamp();
+                let appeal_deadline = now.saturating_add(read_config(env).appeal_window);
+                dispute.resolution = DisputeOutcome::Provisional(resolution.clone());
+                dispute.resolver = Some(admin.clone());
+                dispute.resolved_at = Some(now);
+                dispute.appeal_deadline = Some(appeal_deadline);
+                write_dispute(env, &dispute);
+
+                // Publish provisional resolution event
+                env.events().publish(
+                    (symbol_short!("dispute"), symbol_short!("provision")),
+                    (order.id, resolution, admin, appeal_deadline)
+                );
+                return Ok(());
+            }
+            write_dispute(env, &dispute);
+        }
+
+        Self::pay_out_resolution(env, order, resolution, admin)
+    }
+
+    // Pays out a dispute resolution and closes the dispute
+    pub(crate) fn pay_out_resolution(env: &Env, order: &mut Order, resolution: DisputeResolution, admin: Address) -> Result<(), FastBukaError> {
+        // 1. Get token client
+        let token_client = TokenClient::new(env, &order.token);
         let contract_balance = token_client.balance(&env.current_contract_address());
 
         if order.amount > contract_balance {
             return Err(FastBukaError::InsufficientFundsInContract);
         }
 
-        match resolution {
+        // Platform share of the escrow, sent to the treasury once the parties are paid
+        let platform_amount = match &resolution {
             DisputeResolution::VendorFault => {
                 // Get rider fee
                 let rider_amount = order.rider_fee;
                 
-                // Calculate customer refund amount (total minus rider fee)
+                // Calculate customer refund amount (total minus rider fee and platform fee)
                 let customer_amount = order.amount
                     .checked_sub(rider_amount)
+                    .and_then(|amount| amount.checked_sub(order.platform_fee))
                     .ok_or(FastBukaError::CalculationError)?;
     
                 // Transfer to customer
@@ -143,7 +713,7 @@ impl AdminOperations for FastBukaContract {
                     &customer_amount
                 );
 
-                if let Err(_) = transfer_result {
+                if transfer_result.is_err() {
                     return Err(FastBukaError::CustomerPaymentFailed);
                 }
     
@@ -154,18 +724,20 @@ impl AdminOperations for FastBukaContract {
                         rider,
                         &rider_amount
                     );
-                    if let Err(_) = rider_transfer_payment {
+                    if rider_transfer_payment.is_err() {
                         return Err(FastBukaError::RiderPaymentFailed);
                     }
                 }
+                order.platform_fee
             },
             DisputeResolution::RiderFault => {
                 // Get rider fee (this goes back to customer)
                 let rider_amount = order.rider_fee;
                 
-                // Calculate vendor amount (total minus rider fee)
+                // Calculate vendor amount (total minus rider fee and platform fee)
                 let vendor_amount = order.amount
                     .checked_sub(rider_amount)
+                    .and_then(|amount| amount.checked_sub(order.platform_fee))
                     .ok_or(FastBukaError::CalculationError)?;
     
                 // Transfer rider fee back to customer
@@ -174,7 +746,7 @@ impl AdminOperations for FastBukaContract {
                     &order.user,
                     &rider_amount
                 );
-                if let Err(_) = transfer_payment {
+                if transfer_payment.is_err() {
                     return Err(FastBukaError::CustomerPaymentFailed);
                 }
                 
@@ -182,29 +754,31 @@ impl AdminOperations for FastBukaContract {
                 // Pay vendor
                 let vendor_payment = token_client.try_transfer(
                     &env.current_contract_address(),
-                    &order.vendor,
+                    &vendor_payout_address(env, &order.vendor),
                     &vendor_amount
                 );
-                if let Err(_) = vendor_payment {
+                if vendor_payment.is_err() {
                     return Err(FastBukaError::VendorPaymentFailed);
                 }
+                order.platform_fee
             },
             DisputeResolution::CustomerFault => {
                 // Get rider fee
                 let rider_amount = order.rider_fee;
                 
-                // Calculate vendor amount (total minus rider fee)
+                // Calculate vendor amount (total minus rider fee and platform fee)
                 let vendor_amount = order.amount
                     .checked_sub(rider_amount)
+                    .and_then(|amount| amount.checked_sub(order.platform_fee))
                     .ok_or(FastBukaError::CalculationError)?;
     
                 // Pay vendor
                 let vendor_payment = token_client.try_transfer(
                     &env.current_contract_address(),
-                    &order.vendor,
+                    &vendor_payout_address(env, &order.vendor),
                     &vendor_amount
                 );
-                if let Err(_) = vendor_payment {
+                if vendor_payment.is_err() {
                     return Err(FastBukaError::VendorPaymentFailed);
                 }
     
@@ -215,40 +789,146 @@ impl AdminOperations for FastBukaContract {
                         rider,
                         &rider_amount
                     );
-                    if let Err(_) = rider_payment {
+                    if rider_payment.is_err() {
                         return Err(FastBukaError::RiderPaymentFailed);
                     }
                 }
+                order.platform_fee
+            },
+            DisputeResolution::Custom(split) => {
+                // Shares were checked against the order when the resolution was proposed
+                if split.customer_amount > 0 {
+                    let customer_payment = token_client.try_transfer(
+                        &env.current_contract_address(),
+                        &order.user,
+                        &split.customer_amount
+                    );
+                    if customer_payment.is_err() {
+                        return Err(FastBukaError::CustomerPaymentFailed);
+                    }
+                }
+
+                if split.vendor_amount > 0 {
+                    let vendor_payment = token_client.try_transfer(
+                        &env.current_contract_address(),
+                        &vendor_payout_address(env, &order.vendor),
+                        &split.vendor_amount
+                    );
+                    if vendor_payment.is_err() {
+                        return Err(FastBukaError::VendorPaymentFailed);
+                    }
+                }
+
+                if split.rider_amount > 0 {
+                    let rider = order.rider.as_ref().ok_or(FastBukaError::InvalidSplit)?;
+                    let rider_payment = token_client.try_transfer(
+                        &env.current_contract_address(),
+                        rider,
+                        &split.rider_amount
+                    );
+                    if rider_payment.is_err() {
+                        return Err(FastBukaError::RiderPaymentFailed);
+                    }
+                }
+                split.platform_amount
             }
-        }
+        };
+
+        // 2. Collect the platform share
+        pay_treasury(env, order, platform_amount)?;
 
-        // 7. Update order status to resolved
-        order.status = OrderStatus::Resolved;
-        env.storage().persistent().set(&order_id, &order);
+        // 3. Update order status to resolved
+        set_order_status(env, order, OrderStatus::Resolved);
+        write_order(env, order);
 
-        // 8. Remove from disputed orders list
-        let mut disputed_orders: Vec<u128> = env
-        .storage()
-        .persistent()
-        .get(&DataKey::DisputedOrders)
-        .unwrap_or_else(|| Vec::new(&env));
+        // 4. Remove from disputed orders list
+        let mut disputed_orders = read_disputed_orders(env);
+
+        if let Some(index) = disputed_orders.first_index_of(order.id) {
+            disputed_orders.remove(index);
+            write_disputed_orders(env, &disputed_orders);
+        }
 
-        if let Some(index) = disputed_orders.iter().position(|id| id.clone() == order_id) {
-            disputed_orders.remove(index.try_into().unwrap());
-            env.storage().persistent().set(&DataKey::DisputedOrders, &disputed_orders);
+        // 5. Record the outcome; disputes raised before records were kept have none
+        if let Some(mut dispute) = read_dispute(env, order.id) {
+            dispute.resolution = DisputeOutcome::Resolved(resolution.clone());
+            dispute.resolver = Some(admin.clone());
+            dispute.resolved_at = Some(env.ledger().timestamp());
+            write_dispute(env, &dispute);
         }
 
-        // 9. Emit dispute resolved event
+        // 6. Emit dispute resolved event
         env.events().publish(
             (symbol_short!("dispute"), symbol_short!("resolved")),
             DisputeResolvedEvent {
-                order_id,
+                order_id: order.id,
                 resolution,
-                admin
+                admin,
+                platform_fee: platform_amount,
             }
         );
         Ok(())
     }
-    
-   
-}
\ No newline at end of file
+
+    // Records a pending super admin proposal; callers have already checked the proposer's role
+    fn propose_super_admin(env: &Env, caller: Address, new_admin: Address) -> Result<(), FastBukaError> {
+        if has_role(env, &new_admin, Role::SuperAdmin) {
+            return Err(FastBukaError::AlreadyAdmin);
+        }
+
+        write_pending_admin(env, &new_admin, &caller);
+
+        // Publish admin event
+        env.events().publish(
+            (symbol_short!("admin"), symbol_short!("proposed")),
+            (new_admin, caller)
+        );
+
+        Ok(())
+    }
+
+    fn set_vendor_status(env: &Env, admin: Address, vendor: Address, status: VendorStatus) -> Result<(), FastBukaError> {
+        // Verify admin authentication
+        admin.require_auth();
+        require_role(env, &admin, Role::OnboardingManager)?;
+
+        let mut profile = read_vendor(env, &vendor).ok_or(FastBukaError::VendorNotRegistered)?;
+        profile.status = status.clone();
+        write_vendor(env, &vendor, &profile);
+
+        // Publish status event
+        env.events().publish(
+            (symbol_short!("vendor"), symbol_short!("status")),
+            VendorStatusUpdatedEvent {
+                vendor,
+                status,
+                admin,
+            }
+        );
+
+        Ok(())
+    }
+
+
+    fn set_rider_status(env: &Env, admin: Address, rider: Address, status: RiderStatus) -> Result<(), FastBukaError> {
+        // Verify admin authentication
+        admin.require_auth();
+        require_role(env, &admin, Role::OnboardingManager)?;
+
+        let mut profile = read_rider(env, &rider).ok_or(FastBukaError::RiderNotRegistered)?;
+        profile.status = status.clone();
+        write_rider(env, &rider, &profile);
+
+        // Publish status event
+        env.events().publish(
+            (symbol_short!("rider"), symbol_short!("status")),
+            RiderStatusUpdatedEvent {
+                rider,
+                status,
+                admin,
+            }
+        );
+
+        Ok(())
+    }
+}
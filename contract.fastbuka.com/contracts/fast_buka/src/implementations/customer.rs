@@ -1,10 +1,13 @@
 use crate::{
-    datatypes::{FastBukaError, Order, OrderStatus, DataKey},
+    config::{dispute_window_closed, read_config, require_not_paused},
+    datatypes::{Dispute, DisputeEvent, DisputeOutcome, DisputeParty, Evidence, FastBukaError, Order, OrderIndex, OrderPage, OrderStatus, PauseGroup},
+    index::{read_order_page, set_order_status},
     interface::CustomerOperations,
+    storage::{read_disputed_orders, read_order, write_dispute, write_disputed_orders, write_order},
     FastBukaContract, FastBukaContractClient, FastBukaContractArgs
 };
 
-use soroban_sdk::{contractimpl, Address, Env, String, Symbol, Vec};
+use soroban_sdk::{contractimpl, Address, BytesN, Env, String, Symbol, Vec};
 
 
 
@@ -12,32 +15,41 @@ use soroban_sdk::{contractimpl, Address, Env, String, Symbol, Vec};
 #[contractimpl]
 impl CustomerOperations for FastBukaContract {
 
-    fn get_confirmation_number_customer(
+    // Commit the code the customer will give the rider at the door. The customer picks the
+    // code and a salt off-chain and submits sha256(salt || code), so the code itself never
+    // appears on-chain. Committing again replaces the earlier code
+    fn set_delivery_code(
         env: Env,
         customer: Address,
         order_id: u128,
-    ) -> Result<u32, FastBukaError> {
+        code_hash: BytesN<32>,
+    ) -> Result<(), FastBukaError> {
         // Verify customer's authorization
         customer.require_auth();
 
+        require_not_paused(&env, PauseGroup::Pickups)?;
+
         // Get order from storage
-        let order: Order = env.storage()
-            .persistent()
-            .get(&order_id)
-            .ok_or(FastBukaError::OrderNotFound)?;
+        let mut order: Order = read_order(&env, order_id).ok_or(FastBukaError::OrderNotFound)?;
 
         // Check if the caller is the order's customer
         if customer != order.user {
             return Err(FastBukaError::UnauthorizedAccess);
         }
 
-        // Check if order is in ReadyForPickup status
-        if order.status != OrderStatus::ReadyForPickup {
-            return Err(FastBukaError::OrderNotReady);
+        // Code can be set any time before the rider hands the food over
+        if order.status != OrderStatus::Waiting
+            && order.status != OrderStatus::ReadyForPickup
+            && order.status != OrderStatus::PickedUp
+        {
+            return Err(FastBukaError::InvalidStatus);
         }
 
-        // Return confirmation number if it exists
-        order.confirmation_number.ok_or(FastBukaError::InvalidConfirmationNumber)
+        order.delivery_code_hash = Some(code_hash.into());
+        order.delivery_code_length = read_config(&env).code_length;
+        write_order(&env, &order);
+
+        Ok(())
     }
 
 
@@ -50,10 +62,7 @@ impl CustomerOperations for FastBukaContract {
         customer.require_auth();
 
         // Get order from storage
-        let order: Order = env.storage()
-            .persistent()
-            .get(&order_id)
-            .ok_or(FastBukaError::OrderNotFound)?;
+        let order: Order = read_order(&env, order_id).ok_or(FastBukaError::OrderNotFound)?;
 
         // Check if the caller is the order's customer
         if customer != order.user {
@@ -70,23 +79,24 @@ impl CustomerOperations for FastBukaContract {
         order_id: u128,
         address: Address,
         reason: String,
+        evidence: Vec<Evidence>,
     ) -> Result<(), FastBukaError> {
         // Verify caller's authorization
         address.require_auth();
 
+        require_not_paused(&env, PauseGroup::DisputeResolution)?;
+
         // Get order from storage
-        let mut order: Order = env.storage()
-            .persistent()
-            .get(&order_id)
-            .ok_or(FastBukaError::OrderNotFound)?;
+        let mut order: Order = read_order(&env, order_id).ok_or(FastBukaError::OrderNotFound)?;
 
         // Check if caller is the order's customer
         if address != order.user {
             return Err(FastBukaError::UnauthorizedAccess);
         }
 
-        // Check if order is in a valid status for dispute
-        if order.status != OrderStatus::Delivered {
+        // Check if order is in a valid status for dispute. Picked-up orders can be disputed
+        // too, so a delivery that never completes doesn't hold the escrow forever
+        if order.status != OrderStatus::Delivered && order.status != OrderStatus::PickedUp {
             return Err(FastBukaError::OrderNotDelivered);
         }
 
@@ -100,25 +110,57 @@ impl CustomerOperations for FastBukaContract {
             return Err(FastBukaError::DisputeAlreadyExists);
         }
 
+        // Disputes can only be raised within the confirmation window
+        if dispute_window_closed(&env, &order) {
+            return Err(FastBukaError::DeadlinePassed);
+        }
+
         // Update order status to Disputed
-        order.status = OrderStatus::Disputed;
-        env.storage().persistent().set(&order_id, &order);
+        set_order_status(&env, &mut order, OrderStatus::Disputed);
+        write_order(&env, &order);
 
         // Add order ID to the disputed orders list
-        let mut disputed_orders: Vec<u128> = env.storage().persistent()
-        .get(&DataKey::DisputedOrders)
-        .unwrap_or_else(|| Vec::new(&env));
+        let mut disputed_orders = read_disputed_orders(&env);
 
         if !disputed_orders.contains(&order_id) {
             disputed_orders.push_back(order_id);
-            env.storage().persistent().set(&DataKey::DisputedOrders, &disputed_orders);
+            write_disputed_orders(&env, &disputed_orders);
         }
 
+        // Keep a record of the dispute for arbitrators
+        write_dispute(&env, &Dispute {
+            order_id,
+            initiator: address.clone(),
+            initiator_role: DisputeParty::Customer,
+            reason: reason.clone(),
+            evidence,
+            raised_at: env.ledger().timestamp(),
+            response_deadline: env.ledger().timestamp().saturating_add(read_config(&env).dispute_response_window),
+            responses: Vec::new(&env),
+            resolution: DisputeOutcome::Pending,
+            resolver: None,
+            approvers: Vec::new(&env),
+            resolved_at: None,
+            appeal_deadline: None,
+            appellant: None,
+            appeal_bond: 0,
+        });
+
         // Publish dispute event
         env.events().publish(
             (Symbol::new(&env, "dispute_raised"), order_id),
-            (address, reason.clone())
+            DisputeEvent {
+                order_id,
+                initiator: address,
+                reason,
+            }
         );
         Ok(())
     }
+
+
+    // Orders placed by the customer, oldest first
+    fn get_customer_orders(env: Env, customer: Address, cursor: u32, limit: u32) -> OrderPage {
+        read_order_page(&env, &OrderIndex::Customer(customer), cursor, limit)
+    }
 }
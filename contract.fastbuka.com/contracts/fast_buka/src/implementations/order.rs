@@ -1,10 +1,16 @@
 use crate::{
-    datatypes::{DataKey, FastBukaError, Order, OrderStatus},
+    config::{assignment_timed_out, can_appeal, dispute_respondents, order_party, read_config, read_legacy_order_count, read_token_config, require_not_paused},
+    datatypes::{DataKey, DisputeOutcome, DisputeResponse, Evidence, FastBukaError, Order, OrderCancelledEvent, OrderIndex, OrderPage, PauseGroup, OrderExpiredEvent, OrderStatus, RejectionReason, RiderAssignedEvent, Role, MAX_FEE_BPS, MAX_PAGE_LIMIT},
+    escrow::{calculate_platform_fee, refund_customer, settle_order},
+    index::{bump_order_indexes, index_new_order, read_order_page, set_order_status},
     interface::OrderManagement,
+    registry::{require_active_rider, require_approved_vendor},
+    roles::has_role,
+    storage::{bump_instance, bump_order_record, bump_persistent, read_dispute, read_order, read_rejection_reason, write_dispute, write_order},
     FastBukaContract, FastBukaContractClient, FastBukaContractArgs
 };
 use soroban_sdk::token::Client as TokenClient;
-use soroban_sdk::{contractimpl, symbol_short, Address, Env, Vec};
+use soroban_sdk::{contractimpl, symbol_short, Address, Env, String, Vec};
 
 
 
@@ -12,8 +18,12 @@ use soroban_sdk::{contractimpl, symbol_short, Address, Env, Vec};
 impl OrderManagement for FastBukaContract {
    
 
+    // Falls back to the pre-version 2 counter until `migrate` has copied it over
     fn get_order_count(env: &Env) -> u128 {
-        env.storage().persistent().get(&DataKey::OrderCounter).unwrap_or(0)
+        env.storage()
+            .instance()
+            .get(&DataKey::OrderCounter)
+            .unwrap_or_else(|| read_legacy_order_count(env))
     }
 
     fn create_order(
@@ -27,8 +37,23 @@ impl OrderManagement for FastBukaContract {
         // 1. Authentication
         user.require_auth();
 
+        require_not_paused(&env, PauseGroup::NewOrders)?;
+
+        // Only allowlisted tokens are ever called into
+        let token_config = read_token_config(&env, &token)
+            .filter(|config| config.enabled)
+            .ok_or(FastBukaError::UnsupportedToken)?;
+        if total_amount < token_config.min_amount || total_amount > token_config.max_amount {
+            return Err(FastBukaError::InvalidAmount);
+        }
+
+        // Escrow can only be addressed to approved vendors
+        require_approved_vendor(&env, &vendor)?;
+
         // add customer  address to the datakey
-        env.storage().persistent().set(&DataKey::Customer(user.clone()), &true);
+        let customer_key = DataKey::Customer(user.clone());
+        env.storage().persistent().set(&customer_key, &true);
+        bump_persistent(&env, &customer_key);
 
         // 2. Get timestamp first
         let timestamp = env.ledger().timestamp();
@@ -48,11 +73,17 @@ impl OrderManagement for FastBukaContract {
         if total_amount <= 0 {
             return Err(FastBukaError::InvalidAmount);
         }
+        if rider_fee < 0 || rider_fee > total_amount {
+            return Err(FastBukaError::InvalidAmount);
+        }
+
+        // Platform commission is fixed when the order is placed
+        let platform_fee = calculate_platform_fee(&env, &token, total_amount, rider_fee)?;
 
         // 6 Transfer tokens
         let transfer_result =
-            token_client.transfer(&user, &env.current_contract_address(), &total_amount);
-        if transfer_result != () {
+            token_client.try_transfer(&user, &env.current_contract_address(), &total_amount);
+        if transfer_result.is_err() {
             return Err(FastBukaError::DepositPaymentFailed);
         }
 
@@ -64,17 +95,31 @@ impl OrderManagement for FastBukaContract {
             vendor,
             amount: total_amount,
             rider_fee,
+            platform_fee,
             status: OrderStatus::Waiting,
             rider: None,
+            assigned_rider: None,
+            assigned_at: None,
+            assignment_accepted: false,
             created_at: timestamp,
-            confirmation_number: None,
+            ready_at: None,
+            delivered_at: None,
+            pickup_code_hash: None,
+            delivery_code_hash: None,
+            pickup_code_length: 0,
+            delivery_code_length: 0,
+            failed_delivery_attempts: 0,
+            delivery_locked_until: 0,
+            failed_pickup_attempts: 0,
+            pickup_locked_until: 0,
         };
 
         // 8. Store order using Symbol ID
-        env.storage().persistent().set(&count, &order);
+        write_order(&env, &order);
+        index_new_order(&env, &order);
 
         // 9. Update order count
-        env.storage().persistent().set(&DataKey::OrderCounter, &count);
+        env.storage().instance().set(&DataKey::OrderCounter, &count);
 
         // Publish event
         env.events()
@@ -85,21 +130,23 @@ impl OrderManagement for FastBukaContract {
     }
 
     fn get_order(env: Env, order_id: u128) -> Result<Order, FastBukaError> {
-        env.storage()
-            .persistent()
-            .get(&order_id)
-            .ok_or(FastBukaError::OrderNotFound)
+        read_order(&env, order_id).ok_or(FastBukaError::OrderNotFound)
+    }
+
+
+    // Reason the vendor gave for rejecting the order, if they did
+    fn get_rejection_reason(env: Env, order_id: u128) -> Option<RejectionReason> {
+        read_rejection_reason(&env, order_id)
     }
 
 
 
     fn user_confirms_order(env: Env, order_id: u128, customer: Address) -> Result<(), FastBukaError> {
+        // Verify customer's authorization
+        customer.require_auth();
+
         // 1. Get order
-        let mut order: Order = env
-            .storage()
-            .persistent()
-            .get(&order_id)
-            .ok_or(FastBukaError::OrderNotFound)?;
+        let mut order: Order = read_order(&env, order_id).ok_or(FastBukaError::OrderNotFound)?;
     
         // get customer address
         if order.user != customer {
@@ -116,84 +163,345 @@ impl OrderManagement for FastBukaContract {
             return Err(FastBukaError::OrderNotDelivered);
         }
     
-        // 4. get token and its client
-        let token = order.token.clone();
-        let token_client = TokenClient::new(&env, &token);
-    
-        let contract_balance = token_client.balance(&env.current_contract_address());
-        if order.amount > contract_balance {
-            return Err(FastBukaError::InsufficientFundsInContract);
+        // 4. pay vendor and rider and mark the order completed
+        settle_order(&env, &mut order)
+    }
+
+
+
+    // Orders are numbered from 1, so position `cursor` holds order `cursor + 1`
+    fn get_all_orders(env: Env, cursor: u32, limit: u32) -> OrderPage {
+        let count = Self::get_order_count(&env);
+        let end = (cursor as u128)
+            .saturating_add(limit.min(MAX_PAGE_LIMIT) as u128)
+            .min(count);
+
+        let mut orders = Vec::new(&env);
+        let mut current_id = cursor as u128 + 1;
+        while current_id <= end {
+            if let Some(order) = read_order(&env, current_id) {
+                orders.push_back(order);
+            }
+            current_id += 1;
         }
-    
-        // 5. get rider fee
-        let rider_amount = order.rider_fee;
-    
-        // 6. get vendor total money
-        let vendor_amount = order
-            .amount
-            .checked_sub(rider_amount)
-            .ok_or(FastBukaError::CalculationError)?;
-    
-        // 7. transfer money to vendor
-        let transfer_result = token_client.try_transfer(
-            &env.current_contract_address(), 
-            &order.vendor.clone(), 
-            &vendor_amount
+
+        OrderPage {
+            orders,
+            next_cursor: if end < count { Some(end as u32) } else { None },
+        }
+    }
+
+
+    fn get_orders_by_status(env: Env, status: OrderStatus, cursor: u32, limit: u32) -> OrderPage {
+        read_order_page(&env, &OrderIndex::Status(status), cursor, limit)
+    }
+
+
+
+    fn cancel_order(env: Env, order_id: u128, customer: Address) -> Result<(), FastBukaError> {
+        // 1. Authentication
+        customer.require_auth();
+
+        // 2. Get order
+        let mut order: Order = read_order(&env, order_id).ok_or(FastBukaError::OrderNotFound)?;
+
+        // 3. Only the customer who placed the order can cancel it
+        if order.user != customer {
+            return Err(FastBukaError::NotACustomer);
+        }
+
+        // 4. Cancellation is only allowed before the vendor accepts the order
+        if order.status != OrderStatus::Waiting {
+            return Err(FastBukaError::OrderNotCancellable);
+        }
+
+        // 5. Refund the full escrowed amount to the customer
+        refund_customer(&env, &order)?;
+
+        // 6. update status
+        set_order_status(&env, &mut order, OrderStatus::Cancelled);
+        write_order(&env, &order);
+
+        // Publish event
+        env.events().publish(
+            (symbol_short!("order"), symbol_short!("cancelled")),
+            OrderCancelledEvent {
+                order_id,
+                user: customer,
+                amount: order.amount,
+            }
         );
-        
-        if let Err(_) = transfer_result {
-            return Err(FastBukaError::VendorPaymentFailed);
+
+        Ok(())
+    }
+
+
+
+    // Permissionless refund of orders left unattended past their deadline
+    fn expire_order(env: Env, order_id: u128) -> Result<(), FastBukaError> {
+        // 1. Get order
+        let mut order: Order = read_order(&env, order_id).ok_or(FastBukaError::OrderNotFound)?;
+
+        // 2. Work out the deadline for the current status
+        let config = read_config(&env);
+        let deadline = match order.status {
+            OrderStatus::Waiting => order.created_at.saturating_add(config.waiting_timeout),
+            OrderStatus::ReadyForPickup => order
+                .ready_at
+                .unwrap_or(order.created_at)
+                .saturating_add(config.pickup_timeout),
+            _ => return Err(FastBukaError::InvalidStatus),
+        };
+
+        // 3. Check the deadline has passed
+        if env.ledger().timestamp() < deadline {
+            return Err(FastBukaError::DeadlineNotReached);
         }
-    
-        // 8. transfer money to rider
-        let rider_address = order.rider.as_ref().ok_or(FastBukaError::OrderNotFound)?;
-        let transfer_result = token_client.try_transfer(
-            &env.current_contract_address(), 
-            rider_address, 
-            &rider_amount
+
+        // 4. Refund the full escrowed amount to the customer
+        refund_customer(&env, &order)?;
+
+        // 5. update status
+        let previous_status = order.status.clone();
+        set_order_status(&env, &mut order, OrderStatus::Expired);
+        write_order(&env, &order);
+
+        // Publish event
+        env.events().publish(
+            (symbol_short!("order"), symbol_short!("expired")),
+            OrderExpiredEvent {
+                order_id,
+                user: order.user,
+                amount: order.amount,
+                previous_status,
+            }
         );
-        
-        if let Err(_) = transfer_result {
-            return Err(FastBukaError::RiderPaymentFailed);
+
+        Ok(())
+    }
+
+
+
+    // Permissionless payout of delivered orders once the customer confirmation window has closed
+    fn finalize_order(env: Env, order_id: u128) -> Result<(), FastBukaError> {
+        // 1. Get order
+        let mut order: Order = read_order(&env, order_id).ok_or(FastBukaError::OrderNotFound)?;
+
+        // 2. Disputed or already settled orders cannot be finalized
+        if order.status != OrderStatus::Delivered {
+            return Err(FastBukaError::OrderNotDelivered);
         }
-    
-        // 9. update status
-        order.status = OrderStatus::Completed;
-        env.storage().persistent().set(&order_id, &order);
-    
+
+        // 3. Check the confirmation window has closed
+        let config = read_config(&env);
+        let deadline = order
+            .delivered_at
+            .ok_or(FastBukaError::OrderNotDelivered)?
+            .saturating_add(config.confirmation_window);
+        if env.ledger().timestamp() < deadline {
+            return Err(FastBukaError::DeadlineNotReached);
+        }
+
+        // 4. pay vendor and rider and mark the order completed
+        settle_order(&env, &mut order)
+    }
+
+
+
+    // Vendor or dispatcher nominates the rider who will collect the order
+    fn assign_rider(env: Env, order_id: u128, caller: Address, rider: Address) -> Result<(), FastBukaError> {
+        // 1. Authentication
+        caller.require_auth();
+
+        require_not_paused(&env, PauseGroup::Pickups)?;
+
+        // 2. Get order
+        let mut order: Order = read_order(&env, order_id).ok_or(FastBukaError::OrderNotFound)?;
+
+        // 3. Only the order's vendor or a dispatcher can assign riders
+        let is_dispatcher = has_role(&env, &caller, Role::Dispatcher);
+        if caller != order.vendor && !is_dispatcher {
+            return Err(FastBukaError::UnauthorizedAccess);
+        }
+
+        // 4. Riders can only be assigned before pickup
+        if order.status != OrderStatus::Waiting && order.status != OrderStatus::ReadyForPickup {
+            return Err(FastBukaError::InvalidStatus);
+        }
+
+        // 5. Vendors can only reassign once the current rider declined or timed out
+        if order.assigned_rider.is_some() && !is_dispatcher && !assignment_timed_out(&env, &order) {
+            return Err(FastBukaError::RiderAlreadyAssigned);
+        }
+
+        // 6. The nominated rider must be able to take orders
+        require_active_rider(&env, &rider)?;
+
+        order.assigned_rider = Some(rider.clone());
+        order.assigned_at = Some(env.ledger().timestamp());
+        order.assignment_accepted = false;
+        write_order(&env, &order);
+
+        // Publish event
+        env.events().publish(
+            (symbol_short!("rider"), symbol_short!("assigned")),
+            RiderAssignedEvent {
+                order_id,
+                rider,
+                assigned_by: caller,
+            }
+        );
+
         Ok(())
     }
 
 
 
-    fn get_all_orders(env: Env) -> Result<Vec<Order>, FastBukaError> {
-        // 1. Get total number of orders from our counter
-        let count = Self::get_order_count(&env);
-        
-        // 2. Create a new vector that will hold our orders
-        // The vector needs to be initialized with the environment
-        let mut orders = Vec::new(&env);
-    
-        // 3. Loop through all possible order IDs (from 1 to count)
-        let mut current_id: u128 = 1;
-        while current_id <= count {
-            // 4. Try to get each order from persistent storage
-            // The get<K, V> method takes two type parameters:
-            // K: the key type (u128 in our case)
-            // V: the value type (Order in our case)
-            if let Some(order) = env.storage().persistent().get::<u128, Order>(&current_id) {
-                // 5. If order exists, add it to our vector
-                orders.push_back(order);
+    // Permissionless keeper entrypoint that keeps an order and its index entries from being archived
+    fn bump_order(env: Env, order_id: u128) -> Result<(), FastBukaError> {
+        let order = read_order(&env, order_id).ok_or(FastBukaError::OrderNotFound)?;
+
+        bump_order_indexes(&env, &order);
+        bump_order_record(&env, &DataKey::Dispute(order_id));
+        bump_order_record(&env, &DataKey::RejectionReason(order_id));
+        bump_instance(&env);
+
+        Ok(())
+    }
+
+
+    // The losing party posts a bond to freeze a provisional resolution and escalate it
+    fn appeal_resolution(env: Env, order_id: u128, appellant: Address) -> Result<(), FastBukaError> {
+        // 1. Verify appellant's authorization
+        appellant.require_auth();
+
+        require_not_paused(&env, PauseGroup::DisputeResolution)?;
+
+        // 2. Get the dispute awaiting appeals
+        let order = read_order(&env, order_id).ok_or(FastBukaError::OrderNotFound)?;
+        if order.status != OrderStatus::Disputed {
+            return Err(FastBukaError::DisputeNotFound);
+        }
+        let mut dispute = read_dispute(&env, order_id).ok_or(FastBukaError::DisputeNotFound)?;
+        let resolution = match &dispute.resolution {
+            DisputeOutcome::Provisional(resolution) => resolution.clone(),
+            _ => return Err(FastBukaError::NotAppealable),
+        };
+
+        // 3. Check the appeal window is still open
+        let appeal_deadline = dispute.appeal_deadline.ok_or(FastBukaError::NotAppealable)?;
+        if env.ledger().timestamp() >= appeal_deadline {
+            return Err(FastBukaError::DeadlinePassed);
+        }
+
+        // 4. Only the losing party can appeal
+        let party = order_party(&order, &appellant).ok_or(FastBukaError::UnauthorizedAccess)?;
+        if !can_appeal(&resolution, &party) {
+            return Err(FastBukaError::UnauthorizedAccess);
+        }
+
+        // 5. Take the appeal bond into escrow
+        let bond = order
+            .amount
+            .checked_mul(read_config(&env).appeal_bond_bps as i128)
+            .map(|bond| bond / MAX_FEE_BPS as i128)
+            .ok_or(FastBukaError::CalculationError)?;
+        if bond > 0 {
+            let token_client = TokenClient::new(&env, &order.token);
+            let transfer_result = token_client.try_transfer(&appellant, &env.current_contract_address(), &bond);
+            if transfer_result.is_err() {
+                return Err(FastBukaError::DepositPaymentFailed);
             }
-            current_id += 1;
         }
-    
-        // 6. Check if we found any orders
-        if orders.is_empty() {
-            return Err(FastBukaError::OrderNotFound);
+
+        dispute.resolution = DisputeOutcome::Appealed(resolution);
+        dispute.appellant = Some(appellant.clone());
+        dispute.appeal_bond = bond;
+        write_dispute(&env, &dispute);
+
+        // Publish appeal event
+        env.events().publish(
+            (symbol_short!("appeal"), symbol_short!("raised")),
+            (order_id, appellant, bond)
+        );
+
+        Ok(())
+    }
+
+
+    // Pays out a provisional resolution nobody appealed once its appeal window has closed
+    fn finalize_resolution(env: Env, order_id: u128) -> Result<(), FastBukaError> {
+        require_not_paused(&env, PauseGroup::DisputeResolution)?;
+
+        let mut order = read_order(&env, order_id).ok_or(FastBukaError::OrderNotFound)?;
+        if order.status != OrderStatus::Disputed {
+            return Err(FastBukaError::DisputeNotFound);
         }
-    
-        // 7. Return the vector of orders
-        Ok(orders)
+        let dispute = read_dispute(&env, order_id).ok_or(FastBukaError::DisputeNotFound)?;
+        let resolution = match dispute.resolution {
+            DisputeOutcome::Provisional(resolution) => resolution,
+            _ => return Err(FastBukaError::NotAppealable),
+        };
+
+        let appeal_deadline = dispute.appeal_deadline.ok_or(FastBukaError::NotAppealable)?;
+        if env.ledger().timestamp() < appeal_deadline {
+            return Err(FastBukaError::DeadlineNotReached);
+        }
+
+        let resolver = dispute.resolver.ok_or(FastBukaError::DisputeNotFound)?;
+        Self::pay_out_resolution(&env, &mut order, resolution, resolver)
+    }
+
+
+    // Lets the parties who did not raise a dispute give their side before it is resolved
+    fn respond_to_dispute(
+        env: Env,
+        order_id: u128,
+        responder: Address,
+        statement: String,
+        evidence: Vec<Evidence>,
+    ) -> Result<(), FastBukaError> {
+        // Verify responder's authorization
+        responder.require_auth();
+
+        require_not_paused(&env, PauseGroup::DisputeResolution)?;
+
+        let order = read_order(&env, order_id).ok_or(FastBukaError::OrderNotFound)?;
+        if order.status != OrderStatus::Disputed {
+            return Err(FastBukaError::DisputeNotFound);
+        }
+        let mut dispute = read_dispute(&env, order_id).ok_or(FastBukaError::DisputeNotFound)?;
+
+        // Work out which side of the order the responder is on
+        let party = order_party(&order, &responder).ok_or(FastBukaError::UnauthorizedAccess)?;
+
+        if !dispute_respondents(&env, &order, &dispute).contains(&party) {
+            return Err(FastBukaError::UnauthorizedAccess);
+        }
+
+        if dispute.responses.iter().any(|response| response.party == party) {
+            return Err(FastBukaError::DuplicateSubmission);
+        }
+
+        if env.ledger().timestamp() >= dispute.response_deadline {
+            return Err(FastBukaError::DeadlinePassed);
+        }
+
+        dispute.responses.push_back(DisputeResponse {
+            party: party.clone(),
+            responder: responder.clone(),
+            statement,
+            evidence,
+            responded_at: env.ledger().timestamp(),
+        });
+        write_dispute(&env, &dispute);
+
+        // Publish response event
+        env.events().publish(
+            (symbol_short!("dispute"), symbol_short!("response")),
+            (order_id, party, responder)
+        );
+
+        Ok(())
     }
-}
\ No newline at end of file
+}
@@ -1,58 +1,82 @@
 use crate::{
-    datatypes::{FastBukaError, Order, OrderStatus, DataKey},
+    codes::commitment_matches,
+    config::{assignment_timed_out, dispute_window_closed, read_config, require_not_paused},
+    datatypes::{Dispute, DisputeEvent, DisputeOutcome, DisputeParty, Evidence, FastBukaError, Order, OrderIndex, OrderPage, OrderStatus, PauseGroup, RiderProfile, RiderStatus, VehicleType},
+    index::{add_to_index, read_order_page, set_order_status},
     interface::RiderOperations,
+    registry::{read_rider, require_active_rider, write_rider},
+    storage::{read_disputed_orders, read_order, write_dispute, write_disputed_orders, write_order},
     FastBukaContract, FastBukaContractArgs, FastBukaContractClient
 };
-use soroban_sdk::{contractimpl, Address, Env, String, Symbol, Vec};
+use soroban_sdk::{contractimpl, symbol_short, Address, Bytes, Env, String, Symbol, Vec};
 
 
 
 #[contractimpl]
 impl RiderOperations for FastBukaContract {
     
-    fn get_confirmation_number_rider(
-        env: Env,
-        order_id: u128,
-    ) -> Result<u32, FastBukaError> {
-        // Get order from storage
-        let order: Order = env.storage()
-            .persistent()
-            .get(&order_id)
-            .ok_or(FastBukaError::OrderNotFound)?;
-
-        // Check if order is in ReadyForPickup status
-        if order.status != OrderStatus::PickedUp {
-            return Err(FastBukaError::OrderNotPickedUp);
-        }
-
-        // Return confirmation number if it exists
-        order.confirmation_number.ok_or(FastBukaError::InvalidConfirmationNumber)
-    }
-
-    
+    // Returns false and records a failed attempt when the code does not match
     fn pickup_order(
         env: Env,
         order_id: u128,
         rider: Address,
-    ) -> Result<(), FastBukaError> {
+        pickup_code: u32,
+        pickup_salt: Bytes,
+    ) -> Result<bool, FastBukaError> {
         // Verify rider's authorization
         rider.require_auth();
 
+        require_not_paused(&env, PauseGroup::Pickups)?;
+
         // Get order from storage
-        let mut order: Order = env.storage()
-            .persistent()
-            .get(&order_id)
-            .ok_or(FastBukaError::OrderNotFound)?;
+        let mut order: Order = read_order(&env, order_id).ok_or(FastBukaError::OrderNotFound)?;
+
+        // Only approved riders who are online can take orders
+        require_active_rider(&env, &rider)?;
 
         // Check if order is ready for pickup
         if order.status != OrderStatus::ReadyForPickup {
             return Err(FastBukaError::OrderNotReady);
         }
 
+        // Only the rider assigned to the order can collect it
+        if order.assigned_rider != Some(rider.clone()) {
+            return Err(FastBukaError::RiderNotAssigned);
+        }
+
+        // Check the order is not locked after too many wrong codes
+        let now = env.ledger().timestamp();
+        if now < order.pickup_locked_until {
+            return Err(FastBukaError::CodeAttemptsExceeded);
+        }
+
+        // The vendor hands the rider the code and salt behind their committed hash
+        let config = read_config(&env);
+        if !commitment_matches(&env, &order.pickup_code_hash, &pickup_salt, pickup_code, order.pickup_code_length)? {
+            // Record the failed attempt and lock the order once the limit is hit.
+            // Returning an error here would roll the counter back.
+            order.failed_pickup_attempts += 1;
+            if order.failed_pickup_attempts >= config.max_code_attempts {
+                order.failed_pickup_attempts = 0;
+                order.pickup_locked_until = now.saturating_add(config.code_lockout);
+            }
+            write_order(&env, &order);
+
+            // Publish failed attempt event
+            env.events().publish(
+                (Symbol::new(&env, "pickup_code_failed"), order_id),
+                (rider, order.pickup_locked_until)
+            );
+
+            return Ok(false);
+        }
+
         // Update order with rider and status
         order.rider = Some(rider.clone());
-        order.status = OrderStatus::PickedUp;
-        env.storage().persistent().set(&order_id, &order);
+        order.failed_pickup_attempts = 0;
+        set_order_status(&env, &mut order, OrderStatus::PickedUp);
+        write_order(&env, &order);
+        add_to_index(&env, &OrderIndex::Rider(rider.clone()), order_id);
 
         // Publish pickup event
         env.events().publish(
@@ -60,33 +84,75 @@ impl RiderOperations for FastBukaContract {
             rider
         );
 
-        Ok(())
+        Ok(true)
     }
 
-    fn rider_confirms_delivery(env: Env, order_id: u128) -> Result<(), FastBukaError> {
+    // Returns false and records a failed attempt when the code does not match
+    fn rider_confirms_delivery(
+        env: Env,
+        order_id: u128,
+        rider: Address,
+        delivery_code: u32,
+        delivery_salt: Bytes,
+    ) -> Result<bool, FastBukaError> {
+        // Verify rider's authorization
+        rider.require_auth();
+
+        require_not_paused(&env, PauseGroup::Pickups)?;
+
         // Get order from storage
-        let mut order: Order = env.storage()
-            .persistent()
-            .get(&order_id)
-            .ok_or(FastBukaError::OrderNotFound)?;
+        let mut order: Order = read_order(&env, order_id).ok_or(FastBukaError::OrderNotFound)?;
+
+        // Check if caller is the rider carrying the order
+        if order.rider != Some(rider.clone()) {
+            return Err(FastBukaError::UnauthorizedAccess);
+        }
 
         // Check if order is in PickedUp status
         if order.status != OrderStatus::PickedUp {
             return Err(FastBukaError::InvalidStatus);
         }
-        
 
-        // Update order status to Delivered
-        order.status = OrderStatus::Delivered;
-        env.storage().persistent().set(&order_id, &order);
+        // Check the order is not locked after too many wrong codes
+        let now = env.ledger().timestamp();
+        if now < order.delivery_locked_until {
+            return Err(FastBukaError::CodeAttemptsExceeded);
+        }
+
+        // The customer hands the rider the code and salt behind their committed hash
+        let config = read_config(&env);
+        if !commitment_matches(&env, &order.delivery_code_hash, &delivery_salt, delivery_code, order.delivery_code_length)? {
+            // Record the failed attempt and lock the order once the limit is hit.
+            // Returning an error here would roll the counter back.
+            order.failed_delivery_attempts += 1;
+            if order.failed_delivery_attempts >= config.max_code_attempts {
+                order.failed_delivery_attempts = 0;
+                order.delivery_locked_until = now.saturating_add(config.code_lockout);
+            }
+            write_order(&env, &order);
+
+            // Publish failed attempt event
+            env.events().publish(
+                (Symbol::new(&env, "delivery_code_failed"), order_id),
+                (rider, order.delivery_locked_until)
+            );
+
+            return Ok(false);
+        }
+
+        // Update order status to Delivered and start the confirmation window
+        set_order_status(&env, &mut order, OrderStatus::Delivered);
+        order.delivered_at = Some(now);
+        order.failed_delivery_attempts = 0;
+        write_order(&env, &order);
 
         // Publish delivery event
         env.events().publish(
             (Symbol::new(&env, "order_delivered"), order_id),
-            order.rider.unwrap()
+            rider
         );
 
-        Ok(())
+        Ok(true)
     }  
 
 
@@ -95,15 +161,15 @@ impl RiderOperations for FastBukaContract {
         order_id: u128,
         address: Address,
         reason: String,
+        evidence: Vec<Evidence>,
     ) -> Result<(), FastBukaError> {
         // Verify caller's authorization
         address.require_auth();
 
+        require_not_paused(&env, PauseGroup::DisputeResolution)?;
+
         // Get order from storage
-        let mut order: Order = env.storage()
-            .persistent()
-            .get(&order_id)
-            .ok_or(FastBukaError::OrderNotFound)?;
+        let mut order: Order = read_order(&env, order_id).ok_or(FastBukaError::OrderNotFound)?;
 
         // Check if caller is the order's customer
         if Some(address.clone()) != order.rider {
@@ -111,8 +177,9 @@ impl RiderOperations for FastBukaContract {
         }
 
 
-        // Check if order is in a valid status for dispute
-        if order.status != OrderStatus::Delivered {
+        // Check if order is in a valid status for dispute. Picked-up orders can be disputed
+        // too, so a delivery that never completes doesn't hold the escrow forever
+        if order.status != OrderStatus::Delivered && order.status != OrderStatus::PickedUp {
             return Err(FastBukaError::OrderNotDelivered);
         }
 
@@ -126,28 +193,191 @@ impl RiderOperations for FastBukaContract {
             return Err(FastBukaError::DisputeAlreadyExists);
         }
 
+        // Disputes can only be raised within the confirmation window
+        if dispute_window_closed(&env, &order) {
+            return Err(FastBukaError::DeadlinePassed);
+        }
+
         // Update order status to Disputed
-        order.status = OrderStatus::Disputed;
-        env.storage().persistent().set(&order_id, &order);
+        set_order_status(&env, &mut order, OrderStatus::Disputed);
+        write_order(&env, &order);
 
 
         // Add order ID to the disputed orders list
-        let mut disputed_orders: Vec<u128> = env.storage().persistent()
-            .get(&DataKey::DisputedOrders)
-            .unwrap_or_else(|| Vec::new(&env));
+        let mut disputed_orders = read_disputed_orders(&env);
 
         if !disputed_orders.contains(&order_id) {
             disputed_orders.push_back(order_id);
-            env.storage().persistent().set(&DataKey::DisputedOrders, &disputed_orders);
+            write_disputed_orders(&env, &disputed_orders);
         }
 
+        // Keep a record of the dispute for arbitrators
+        write_dispute(&env, &Dispute {
+            order_id,
+            initiator: address.clone(),
+            initiator_role: DisputeParty::Rider,
+            reason: reason.clone(),
+            evidence,
+            raised_at: env.ledger().timestamp(),
+            response_deadline: env.ledger().timestamp().saturating_add(read_config(&env).dispute_response_window),
+            responses: Vec::new(&env),
+            resolution: DisputeOutcome::Pending,
+            resolver: None,
+            approvers: Vec::new(&env),
+            resolved_at: None,
+            appeal_deadline: None,
+            appellant: None,
+            appeal_bond: 0,
+        });
+
         // Publish dispute event
         env.events().publish(
             (Symbol::new(&env, "dispute_raised"), order_id),
-            (address, reason.clone())
+            DisputeEvent {
+                order_id,
+                initiator: address,
+                reason,
+            }
         );
         Ok(())
     }
 
-}
 
+    // Register a rider profile, pending admin approval
+    fn register_rider(
+        env: Env,
+        rider: Address,
+        vehicle_type: VehicleType,
+        metadata_uri: String,
+    ) -> Result<(), FastBukaError> {
+        // Verify rider's authorization
+        rider.require_auth();
+
+        require_not_paused(&env, PauseGroup::Onboarding)?;
+
+        if read_rider(&env, &rider).is_some() {
+            return Err(FastBukaError::AlreadyRegistered);
+        }
+
+        let profile = RiderProfile {
+            vehicle_type,
+            metadata_uri,
+            status: RiderStatus::Pending,
+            online: false,
+            registered_at: env.ledger().timestamp(),
+        };
+        write_rider(&env, &rider, &profile);
+
+        // Publish registration event
+        env.events().publish(
+            (symbol_short!("rider"), symbol_short!("register")),
+            rider
+        );
+
+        Ok(())
+    }
+
+
+    fn get_rider(env: Env, rider: Address) -> Result<RiderProfile, FastBukaError> {
+        read_rider(&env, &rider).ok_or(FastBukaError::RiderNotRegistered)
+    }
+
+
+    // Riders go online to receive orders and offline when they stop working
+    fn set_rider_online(env: Env, rider: Address, online: bool) -> Result<(), FastBukaError> {
+        // Verify rider's authorization
+        rider.require_auth();
+
+        require_not_paused(&env, PauseGroup::Pickups)?;
+
+        let mut profile = read_rider(&env, &rider).ok_or(FastBukaError::RiderNotRegistered)?;
+        profile.online = online;
+        write_rider(&env, &rider, &profile);
+
+        // Publish availability event
+        env.events().publish(
+            (symbol_short!("rider"), symbol_short!("online")),
+            (rider, online)
+        );
+
+        Ok(())
+    }
+
+
+    fn accept_assignment(env: Env, order_id: u128, rider: Address) -> Result<(), FastBukaError> {
+        // Verify rider's authorization
+        rider.require_auth();
+
+        require_not_paused(&env, PauseGroup::Pickups)?;
+
+        // Get order from storage
+        let mut order: Order = read_order(&env, order_id).ok_or(FastBukaError::OrderNotFound)?;
+
+        // Check if caller is the assigned rider
+        if order.assigned_rider != Some(rider.clone()) {
+            return Err(FastBukaError::RiderNotAssigned);
+        }
+
+        // Assignments can be accepted until the order is picked up
+        if order.status != OrderStatus::Waiting && order.status != OrderStatus::ReadyForPickup {
+            return Err(FastBukaError::InvalidStatus);
+        }
+
+        // Check the assignment has not lapsed
+        if assignment_timed_out(&env, &order) {
+            return Err(FastBukaError::DeadlinePassed);
+        }
+
+        order.assignment_accepted = true;
+        write_order(&env, &order);
+
+        // Publish acceptance event
+        env.events().publish(
+            (symbol_short!("rider"), symbol_short!("accepted")),
+            (order_id, rider)
+        );
+
+        Ok(())
+    }
+
+
+    fn decline_assignment(env: Env, order_id: u128, rider: Address) -> Result<(), FastBukaError> {
+        // Verify rider's authorization
+        rider.require_auth();
+
+        require_not_paused(&env, PauseGroup::Pickups)?;
+
+        // Get order from storage
+        let mut order: Order = read_order(&env, order_id).ok_or(FastBukaError::OrderNotFound)?;
+
+        // Check if caller is the assigned rider
+        if order.assigned_rider != Some(rider.clone()) {
+            return Err(FastBukaError::RiderNotAssigned);
+        }
+
+        // Declining is only possible before pickup
+        if order.status != OrderStatus::Waiting && order.status != OrderStatus::ReadyForPickup {
+            return Err(FastBukaError::InvalidStatus);
+        }
+
+        // Free the order up for reassignment
+        order.assigned_rider = None;
+        order.assigned_at = None;
+        order.assignment_accepted = false;
+        write_order(&env, &order);
+
+        // Publish decline event
+        env.events().publish(
+            (symbol_short!("rider"), symbol_short!("declined")),
+            (order_id, rider)
+        );
+
+        Ok(())
+    }
+
+
+    // Orders the rider has picked up, oldest first
+    fn get_rider_orders(env: Env, rider: Address, cursor: u32, limit: u32) -> OrderPage {
+        read_order_page(&env, &OrderIndex::Rider(rider), cursor, limit)
+    }
+}
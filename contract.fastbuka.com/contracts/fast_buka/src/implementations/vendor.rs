@@ -1,9 +1,14 @@
 use crate::{
-    datatypes::{FastBukaError, Order, OrderStatus, DataKey},
+    config::{read_config, require_not_paused},
+    datatypes::{FastBukaError, Order, OrderStatus, OrderIndex, OrderPage, OrderRejectedEvent, PauseGroup, RejectionReason, VendorProfile, VendorStatus, MAX_PAGE_LIMIT},
+    escrow::refund_customer,
+    index::{read_index_len, read_order_page, set_order_status},
     interface::VendorOperations,
+    registry::{read_vendor, require_approved_vendor, write_vendor},
+    storage::{read_order, write_order, write_rejection_reason},
     FastBukaContract, FastBukaContractClient, FastBukaContractArgs
 };
-use soroban_sdk::{contractimpl, Address, Env, Vec};
+use soroban_sdk::{contractimpl, symbol_short, Address, BytesN, Env, String, Vec};
 
 
 
@@ -12,65 +17,203 @@ use soroban_sdk::{contractimpl, Address, Env, Vec};
 impl VendorOperations for FastBukaContract {
     // Update order status to ReadyForPickup
 
-    fn update_order_status(env: Env, order_id: u128, vendor: Address) -> Result<u32, FastBukaError> {
-        let mut order: Order = env.storage().persistent().get::<u128, Order>(&order_id)
-            .ok_or_else(|| { FastBukaError::OrderNotFound })?;
+    fn update_order_status(env: Env, order_id: u128, vendor: Address) -> Result<(), FastBukaError> {
+        // Verify vendor's authorization
+        vendor.require_auth();
+
+        require_not_paused(&env, PauseGroup::Pickups)?;
+
+        let mut order: Order = read_order(&env, order_id).ok_or(FastBukaError::OrderNotFound)?;
     
-        if &vendor != &order.vendor {
+        if vendor != order.vendor {
             return Err(FastBukaError::UnauthorizedAccess);
         }
+
+        // Suspended vendors cannot progress orders
+        require_approved_vendor(&env, &vendor)?;
+
+        // Only orders still waiting on the vendor can be marked ready
+        if order.status != OrderStatus::Waiting {
+            return Err(FastBukaError::InvalidStatusTransition);
+        }
     
-        let confirmation_number = Self::generate_confirmation_number(&env);
-        order.confirmation_number = Some(confirmation_number);  // Wrap in Some()
-        order.status = OrderStatus::ReadyForPickup;
+        set_order_status(&env, &mut order, OrderStatus::ReadyForPickup);
+        order.ready_at = Some(env.ledger().timestamp());
     
-        env.storage().persistent().set(&order_id, &order);
+        write_order(&env, &order);
         
-        Ok(confirmation_number)  // Return the raw u32
+        Ok(())
     }
         
 
     
-    fn get_vendor_pending_orders(env: Env, vendor: Address) -> Result<Vec<Order>, FastBukaError> {
-       
-        let count =  env.storage().persistent().get(&DataKey::OrderCounter).unwrap_or(0);
-        let mut vendor_orders = Vec::new(&env); // Initialize vector for vendor orders
-    
-        // Iterate through all order IDs
-        for current_id in 1..=count {
-            // Try to get the order from storage
-            if let Some(order) = env.storage().persistent().get::<u128, Order>(&current_id) {
-                // Check if the vendor matches
-                if order.vendor == vendor {
-                    vendor_orders.push_back(order);
+    // Vendor's orders in any of `statuses` created within [created_from, created_to], one status
+    // after another. `cursor` counts index slots across the statuses, and each call reads at most
+    // `limit` slots, so a page can hold fewer orders than `limit` and still have a next cursor
+    fn get_vendor_pending_orders(
+        env: Env,
+        vendor: Address,
+        statuses: Vec<OrderStatus>,
+        created_from: u64,
+        created_to: u64,
+        cursor: u32,
+        limit: u32,
+    ) -> Result<OrderPage, FastBukaError> {
+        if created_from > created_to {
+            return Err(FastBukaError::InvalidTimeRange);
+        }
+
+        // Read each status once, however often it is listed
+        let mut unique: Vec<OrderStatus> = Vec::new(&env);
+        for status in statuses.iter() {
+            if !unique.contains(&status) {
+                unique.push_back(status);
+            }
+        }
+
+        let end = cursor.saturating_add(limit.min(MAX_PAGE_LIMIT));
+        let mut orders: Vec<Order> = Vec::new(&env);
+        let mut start = 0u32;
+        for status in unique.iter() {
+            let index = OrderIndex::VendorStatus(vendor.clone(), status);
+            let len = read_index_len(&env, &index);
+
+            // Slots of this status that fall inside [cursor, end)
+            let from = cursor.max(start);
+            let to = end.min(start.saturating_add(len));
+            if from < to {
+                let page = read_order_page(&env, &index, from - start, to - from);
+                for order in page.orders.iter() {
+                    if order.created_at >= created_from && order.created_at <= created_to {
+                        orders.push_back(order);
+                    }
                 }
             }
+            start = start.saturating_add(len);
         }
-    
-        // Return error if no orders are found
-        if vendor_orders.is_empty() {
-            return Err(FastBukaError::OrderNotFound);
+
+        Ok(OrderPage {
+            orders,
+            next_cursor: if end < start { Some(end) } else { None },
+        })
+    }
+
+
+    // Orders addressed to the vendor, oldest first
+    fn get_vendor_orders(env: Env, vendor: Address, cursor: u32, limit: u32) -> OrderPage {
+        read_order_page(&env, &OrderIndex::Vendor(vendor), cursor, limit)
+    }
+
+
+    // Decline a waiting order and refund the customer in full
+    fn reject_order(
+        env: Env,
+        order_id: u128,
+        vendor: Address,
+        reason: RejectionReason,
+    ) -> Result<(), FastBukaError> {
+        // Verify vendor's authorization
+        vendor.require_auth();
+
+        let mut order: Order = read_order(&env, order_id).ok_or(FastBukaError::OrderNotFound)?;
+
+        // Check if the caller is the order's vendor
+        if vendor != order.vendor {
+            return Err(FastBukaError::UnauthorizedAccess);
         }
-    
-        Ok(vendor_orders)
+
+        // Only orders the vendor has not accepted yet can be rejected
+        if order.status != OrderStatus::Waiting {
+            return Err(FastBukaError::OrderNotCancellable);
+        }
+
+        // Refund the full escrowed amount to the customer
+        refund_customer(&env, &order)?;
+
+        write_rejection_reason(&env, order_id, &reason);
+        set_order_status(&env, &mut order, OrderStatus::Cancelled);
+        write_order(&env, &order);
+
+        // Publish rejection event
+        env.events().publish(
+            (symbol_short!("order"), symbol_short!("rejected")),
+            OrderRejectedEvent {
+                order_id,
+                vendor,
+                reason,
+            }
+        );
+
+        Ok(())
     }
 
 
-    // Helper function to generate confirmation order
-    fn generate_confirmation_number(env: &Env) -> u32 {
-        // Get current timestamp
-        let timestamp = env.ledger().sequence();
-        // std::println!("Timestamp in function: {}", timestamp);
-    
-        
-        // Get a random component using timestamp
-        let random_component = timestamp % 10000;
-        
-        // Ensure number is between 1000-9999
-        let confirmation = 1000 + (random_component as u32); 
-        confirmation
+    // Register a vendor profile, pending admin approval
+    fn register_vendor(
+        env: Env,
+        vendor: Address,
+        name: String,
+        payout_address: Address,
+        metadata_uri: String,
+    ) -> Result<(), FastBukaError> {
+        // Verify vendor's authorization
+        vendor.require_auth();
+
+        require_not_paused(&env, PauseGroup::Onboarding)?;
+
+        if read_vendor(&env, &vendor).is_some() {
+            return Err(FastBukaError::AlreadyRegistered);
+        }
+
+        let profile = VendorProfile {
+            name,
+            payout_address,
+            metadata_uri,
+            status: VendorStatus::Pending,
+            registered_at: env.ledger().timestamp(),
+        };
+        write_vendor(&env, &vendor, &profile);
+
+        // Publish registration event
+        env.events().publish(
+            (symbol_short!("vendor"), symbol_short!("register")),
+            vendor
+        );
+
+        Ok(())
     }
 
 
-    
-}
\ No newline at end of file
+    fn get_vendor(env: Env, vendor: Address) -> Result<VendorProfile, FastBukaError> {
+        read_vendor(&env, &vendor).ok_or(FastBukaError::VendorNotRegistered)
+    }
+
+
+    // Commit the code the vendor will give the assigned rider at pickup. The vendor picks the
+    // code and a salt off-chain and submits sha256(salt || code). Committing again replaces
+    // the earlier code
+    fn set_pickup_code(env: Env, vendor: Address, order_id: u128, code_hash: BytesN<32>) -> Result<(), FastBukaError> {
+        // Verify vendor's authorization
+        vendor.require_auth();
+
+        require_not_paused(&env, PauseGroup::Pickups)?;
+
+        let mut order: Order = read_order(&env, order_id).ok_or(FastBukaError::OrderNotFound)?;
+
+        // Check if the caller is the order's vendor
+        if vendor != order.vendor {
+            return Err(FastBukaError::UnauthorizedAccess);
+        }
+
+        // Code can be set any time before the rider collects the food
+        if order.status != OrderStatus::Waiting && order.status != OrderStatus::ReadyForPickup {
+            return Err(FastBukaError::InvalidStatus);
+        }
+
+        order.pickup_code_hash = Some(code_hash.into());
+        order.pickup_code_length = read_config(&env).code_length;
+        write_order(&env, &order);
+
+        Ok(())
+    }
+}
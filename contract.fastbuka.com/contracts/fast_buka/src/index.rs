@@ -0,0 +1,132 @@
+use crate::{
+    datatypes::{
+        DataKey, Order, OrderIndex, OrderPage, OrderStatus, MAX_PAGE_LIMIT, ORDER_BUMP_AMOUNT,
+        ORDER_LIFETIME_THRESHOLD,
+    },
+    storage::{bump_persistent, read_order},
+};
+use soroban_sdk::{Env, Vec};
+
+// Indexes are stored one id per entry so no single entry grows with the number of orders.
+// Removal swaps the last id into the freed slot, so status pages are not stable while
+// orders move between statuses.
+
+pub fn read_index_len(e: &Env, index: &OrderIndex) -> u32 {
+    e.storage()
+        .persistent()
+        .get(&DataKey::OrderIndexLen(index.clone()))
+        .unwrap_or(0)
+}
+
+pub fn add_to_index(e: &Env, index: &OrderIndex, order_id: u128) {
+    let storage = e.storage().persistent();
+    if storage.has(&DataKey::OrderIndexPos(index.clone(), order_id)) {
+        return;
+    }
+
+    let len = read_index_len(e, index);
+    storage.set(&DataKey::OrderIndexEntry(index.clone(), len), &order_id);
+    storage.set(&DataKey::OrderIndexPos(index.clone(), order_id), &len);
+    storage.set(&DataKey::OrderIndexLen(index.clone()), &(len + 1));
+    bump_index_slot(e, index, len, order_id);
+    bump_persistent(e, &DataKey::OrderIndexLen(index.clone()));
+}
+
+pub fn remove_from_index(e: &Env, index: &OrderIndex, order_id: u128) {
+    let storage = e.storage().persistent();
+    let pos_key = DataKey::OrderIndexPos(index.clone(), order_id);
+    let Some(pos) = storage.get::<DataKey, u32>(&pos_key) else {
+        return;
+    };
+
+    // Move the last id into the freed slot
+    let last = read_index_len(e, index) - 1;
+    if pos != last {
+        let last_id: u128 = storage
+            .get(&DataKey::OrderIndexEntry(index.clone(), last))
+            .unwrap();
+        storage.set(&DataKey::OrderIndexEntry(index.clone(), pos), &last_id);
+        storage.set(&DataKey::OrderIndexPos(index.clone(), last_id), &pos);
+        bump_index_slot(e, index, pos, last_id);
+    }
+
+    storage.remove(&DataKey::OrderIndexEntry(index.clone(), last));
+    storage.remove(&pos_key);
+    storage.set(&DataKey::OrderIndexLen(index.clone()), &last);
+    bump_persistent(e, &DataKey::OrderIndexLen(index.clone()));
+}
+
+// Index slots live as long as the orders they point to
+fn bump_index_slot(e: &Env, index: &OrderIndex, pos: u32, order_id: u128) {
+    let storage = e.storage().persistent();
+    storage.extend_ttl(
+        &DataKey::OrderIndexEntry(index.clone(), pos),
+        ORDER_LIFETIME_THRESHOLD,
+        ORDER_BUMP_AMOUNT,
+    );
+    storage.extend_ttl(
+        &DataKey::OrderIndexPos(index.clone(), order_id),
+        ORDER_LIFETIME_THRESHOLD,
+        ORDER_BUMP_AMOUNT,
+    );
+}
+
+// Extend every index slot the order occupies
+pub fn bump_order_indexes(e: &Env, order: &Order) {
+    let mut indexes = Vec::new(e);
+    indexes.push_back(OrderIndex::Customer(order.user.clone()));
+    indexes.push_back(OrderIndex::Vendor(order.vendor.clone()));
+    indexes.push_back(OrderIndex::Status(order.status.clone()));
+    indexes.push_back(OrderIndex::VendorStatus(order.vendor.clone(), order.status.clone()));
+    if let Some(rider) = &order.rider {
+        indexes.push_back(OrderIndex::Rider(rider.clone()));
+    }
+
+    for index in indexes.iter() {
+        let pos_key = DataKey::OrderIndexPos(index.clone(), order.id);
+        if let Some(pos) = e.storage().persistent().get::<DataKey, u32>(&pos_key) {
+            bump_index_slot(e, &index, pos, order.id);
+            bump_persistent(e, &DataKey::OrderIndexLen(index));
+        }
+    }
+}
+
+// Add a newly created order to its customer, vendor and status indexes
+pub fn index_new_order(e: &Env, order: &Order) {
+    add_to_index(e, &OrderIndex::Customer(order.user.clone()), order.id);
+    add_to_index(e, &OrderIndex::Vendor(order.vendor.clone()), order.id);
+    add_to_index(e, &OrderIndex::Status(order.status.clone()), order.id);
+    add_to_index(e, &OrderIndex::VendorStatus(order.vendor.clone(), order.status.clone()), order.id);
+}
+
+// Move an order to a new status, keeping the status indexes in step
+pub fn set_order_status(e: &Env, order: &mut Order, status: OrderStatus) {
+    remove_from_index(e, &OrderIndex::Status(order.status.clone()), order.id);
+    remove_from_index(e, &OrderIndex::VendorStatus(order.vendor.clone(), order.status.clone()), order.id);
+    add_to_index(e, &OrderIndex::Status(status.clone()), order.id);
+    add_to_index(e, &OrderIndex::VendorStatus(order.vendor.clone(), status.clone()), order.id);
+    order.status = status;
+}
+
+// Up to `limit` orders from the index, starting at position `cursor`
+pub fn read_order_page(e: &Env, index: &OrderIndex, cursor: u32, limit: u32) -> OrderPage {
+    let len = read_index_len(e, index);
+    let end = cursor.saturating_add(limit.min(MAX_PAGE_LIMIT)).min(len);
+
+    let mut orders = Vec::new(e);
+    for pos in cursor..end {
+        let order_id: u128 = e
+            .storage()
+            .persistent()
+            .get(&DataKey::OrderIndexEntry(index.clone(), pos))
+            .unwrap();
+        if let Some(order) = read_order(e, order_id) {
+            orders.push_back(order);
+        }
+    }
+
+    OrderPage {
+        orders,
+        next_cursor: if end < len { Some(end) } else { None },
+    }
+}
@@ -1,6 +1,6 @@
-use crate::datatypes::{FastBukaError, Order, OrderStatus, DisputeResolution};
+use crate::datatypes::{Dispute, Evidence, FastBukaError, Order, OrderPage, OrderStatus, PauseGroup, Role, DisputeResolution, RejectionReason, ResolutionProposal, EscrowConfig, TokenConfig, VendorProfile, RiderProfile, VehicleType};
 use soroban_sdk::{
-    Address, Env, Vec, String
+    Address, Bytes, BytesN, Env, Vec, String
 };
 
 
@@ -8,34 +8,85 @@ pub trait OrderManagement {
     fn get_order_count(env: &Env) -> u128; 
     fn create_order(env: Env, user: Address, token: Address, vendor: Address, total_amount: i128, rider_fee: i128) -> Result<u128, FastBukaError>;
     fn get_order(env: Env, order_id: u128) -> Result<Order, FastBukaError>;
+    fn get_rejection_reason(env: Env, order_id: u128) -> Option<RejectionReason>;
     fn user_confirms_order(env: Env, order_id: u128, customer: Address) -> Result<(), FastBukaError>;
-    fn get_all_orders(env: Env) -> Result<Vec<Order>, FastBukaError>;
+    fn get_all_orders(env: Env, cursor: u32, limit: u32) -> OrderPage;
+    fn get_orders_by_status(env: Env, status: OrderStatus, cursor: u32, limit: u32) -> OrderPage;
+    fn cancel_order(env: Env, order_id: u128, customer: Address) -> Result<(), FastBukaError>;
+    fn expire_order(env: Env, order_id: u128) -> Result<(), FastBukaError>;
+    fn finalize_order(env: Env, order_id: u128) -> Result<(), FastBukaError>;
+    fn assign_rider(env: Env, order_id: u128, caller: Address, rider: Address) -> Result<(), FastBukaError>;
+    fn bump_order(env: Env, order_id: u128) -> Result<(), FastBukaError>;
+    fn appeal_resolution(env: Env, order_id: u128, appellant: Address) -> Result<(), FastBukaError>;
+    fn finalize_resolution(env: Env, order_id: u128) -> Result<(), FastBukaError>;
+    fn respond_to_dispute(env: Env, order_id: u128, responder: Address, statement: String, evidence: Vec<Evidence>) -> Result<(), FastBukaError>;
 }
 
 pub trait VendorOperations {
-    fn update_order_status(env: Env, order_id: u128, vendor: Address) -> Result<u32, FastBukaError>;
-    fn get_vendor_pending_orders(env: Env, vendor: Address) -> Result<Vec<Order>, FastBukaError> ;
-    fn generate_confirmation_number(env: &Env) -> u32;
+    fn update_order_status(env: Env, order_id: u128, vendor: Address) -> Result<(), FastBukaError>;
+    fn get_vendor_pending_orders(env: Env, vendor: Address, statuses: Vec<OrderStatus>, created_from: u64, created_to: u64, cursor: u32, limit: u32) -> Result<OrderPage, FastBukaError>;
+    fn reject_order(env: Env, order_id: u128, vendor: Address, reason: RejectionReason) -> Result<(), FastBukaError>;
+    fn register_vendor(env: Env, vendor: Address, name: String, payout_address: Address, metadata_uri: String) -> Result<(), FastBukaError>;
+    fn get_vendor(env: Env, vendor: Address) -> Result<VendorProfile, FastBukaError>;
+    fn get_vendor_orders(env: Env, vendor: Address, cursor: u32, limit: u32) -> OrderPage;
+    fn set_pickup_code(env: Env, vendor: Address, order_id: u128, code_hash: BytesN<32>) -> Result<(), FastBukaError>;
 }
 
 pub trait RiderOperations {
-    fn pickup_order(env: Env, order_id: u128, rider: Address) -> Result<(), FastBukaError>;
-    fn get_confirmation_number_rider(env: Env, order_id: u128) -> Result<u32, FastBukaError>;
-    fn rider_confirms_delivery(env: Env, order_id: u128) ->Result<(), FastBukaError>;
-    fn rider_raise_dispute(env: Env, order_id: u128, address: Address, reason: String) -> Result<(), FastBukaError>;
+    fn pickup_order(env: Env, order_id: u128, rider: Address, pickup_code: u32, pickup_salt: Bytes) -> Result<bool, FastBukaError>;
+    fn rider_confirms_delivery(env: Env, order_id: u128, rider: Address, delivery_code: u32, delivery_salt: Bytes) -> Result<bool, FastBukaError>;
+    fn rider_raise_dispute(env: Env, order_id: u128, address: Address, reason: String, evidence: Vec<Evidence>) -> Result<(), FastBukaError>;
+    fn register_rider(env: Env, rider: Address, vehicle_type: VehicleType, metadata_uri: String) -> Result<(), FastBukaError>;
+    fn get_rider(env: Env, rider: Address) -> Result<RiderProfile, FastBukaError>;
+    fn set_rider_online(env: Env, rider: Address, online: bool) -> Result<(), FastBukaError>;
+    fn accept_assignment(env: Env, order_id: u128, rider: Address) -> Result<(), FastBukaError>;
+    fn decline_assignment(env: Env, order_id: u128, rider: Address) -> Result<(), FastBukaError>;
+    fn get_rider_orders(env: Env, rider: Address, cursor: u32, limit: u32) -> OrderPage;
 }
 
 pub trait CustomerOperations {
-    fn get_confirmation_number_customer(env: Env, customer: Address, order_id: u128) -> Result<u32, FastBukaError>;
+    fn set_delivery_code(env: Env, customer: Address, order_id: u128, code_hash: BytesN<32>) -> Result<(), FastBukaError>;
     fn check_order_status(env: Env, customer: Address, order_id: u128) -> Result<OrderStatus, FastBukaError>;
-    fn customer_raise_dispute(env: Env, order_id: u128, address: Address, reason: String) -> Result<(), FastBukaError>;
+    fn customer_raise_dispute(env: Env, order_id: u128, address: Address, reason: String, evidence: Vec<Evidence>) -> Result<(), FastBukaError>;
+    fn get_customer_orders(env: Env, customer: Address, cursor: u32, limit: u32) -> OrderPage;
 }
 
 
 pub trait AdminOperations {
     fn resolve_dispute(env: Env, order_id: u128, resolution: DisputeResolution, admin: Address) -> Result<(), FastBukaError>;
-    fn add_admin(env: Env, caller: Address, new_admin: Address) -> Result<(), FastBukaError>;
+    fn approve_resolution(env: Env, order_id: u128, arbitrator: Address) -> Result<(), FastBukaError>;
+    fn get_resolution_proposal(env: Env, order_id: u128) -> Option<ResolutionProposal>;
+    fn decide_appeal(env: Env, order_id: u128, arbitrator: Address, resolution: DisputeResolution) -> Result<(), FastBukaError>;
+    fn propose_admin(env: Env, caller: Address, new_admin: Address) -> Result<(), FastBukaError>;
+    fn accept_admin(env: Env, new_admin: Address) -> Result<(), FastBukaError>;
+    fn cancel_admin_proposal(env: Env, caller: Address, new_admin: Address) -> Result<(), FastBukaError>;
+    fn get_pending_admin_proposer(env: Env, new_admin: Address) -> Option<Address>;
     fn get_admins(env: Env) -> Vec<Address>;
     fn remove_admin(env: Env, caller: Address, admin_to_remove: Address) -> Result<(), FastBukaError>;
+    fn grant_role(env: Env, admin: Address, account: Address, role: Role) -> Result<(), FastBukaError>;
+    fn revoke_role(env: Env, admin: Address, account: Address, role: Role) -> Result<(), FastBukaError>;
+    fn has_role(env: Env, account: Address, role: Role) -> bool;
+    fn get_role_members(env: Env, role: Role) -> Vec<Address>;
     fn get_all_disputed_orders(env: Env) -> Vec<u128>;
+    fn get_dispute(env: Env, order_id: u128) -> Result<Dispute, FastBukaError>;
+    fn set_paused(env: Env, admin: Address, group: PauseGroup, paused: bool) -> Result<(), FastBukaError>;
+    fn is_paused(env: Env, group: PauseGroup) -> bool;
+    fn upgrade(env: Env, admin: Address, new_wasm_hash: BytesN<32>) -> Result<(), FastBukaError>;
+    fn migrate(env: Env, admin: Address) -> Result<u32, FastBukaError>;
+    fn get_version(env: Env) -> u32;
+    fn migrate_order_keys(env: Env, admin: Address, start_id: u128, limit: u32) -> Result<u32, FastBukaError>;
+    fn set_config(env: Env, admin: Address, config: EscrowConfig) -> Result<(), FastBukaError>;
+    fn get_config(env: Env) -> EscrowConfig;
+    fn set_treasury(env: Env, admin: Address, treasury: Address) -> Result<(), FastBukaError>;
+    fn get_treasury(env: Env) -> Option<Address>;
+    fn set_platform_fee(env: Env, admin: Address, token: Option<Address>, fee_bps: u32) -> Result<(), FastBukaError>;
+    fn get_platform_fee(env: Env, token: Address) -> u32;
+    fn add_accepted_token(env: Env, admin: Address, token: Address, config: TokenConfig) -> Result<(), FastBukaError>;
+    fn remove_accepted_token(env: Env, admin: Address, token: Address) -> Result<(), FastBukaError>;
+    fn get_accepted_tokens(env: Env) -> Vec<Address>;
+    fn get_token_config(env: Env, token: Address) -> Option<TokenConfig>;
+    fn approve_vendor(env: Env, admin: Address, vendor: Address) -> Result<(), FastBukaError>;
+    fn suspend_vendor(env: Env, admin: Address, vendor: Address) -> Result<(), FastBukaError>;
+    fn approve_rider(env: Env, admin: Address, rider: Address) -> Result<(), FastBukaError>;
+    fn suspend_rider(env: Env, admin: Address, rider: Address) -> Result<(), FastBukaError>;
 }  
\ No newline at end of file
@@ -1,6 +1,7 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, Env, Address, Vec};
-use crate::datatypes::{DataKey};
+use soroban_sdk::{contract, contractimpl, Env, Address};
+use crate::datatypes::{DataKey, CONTRACT_VERSION};
+use crate::roles::{all_roles, grant_role};
 
 #[contract]
 pub struct FastBukaContract;
@@ -8,9 +9,11 @@ pub struct FastBukaContract;
 #[contractimpl]
 impl FastBukaContract {
     pub fn __constructor(env: Env, admin: Address) {
-        let mut admins = Vec::new(&env);
-        admins.push_back(admin);
-        env.storage().persistent().set(&DataKey::Admin, &admins);
+        // The deployer starts with every role and hands them out from there
+        for role in all_roles(&env).iter() {
+            grant_role(&env, &admin, role);
+        }
+        env.storage().instance().set(&DataKey::Version, &CONTRACT_VERSION);
     }
 }
 
@@ -22,8 +25,15 @@ pub use implementations::{
     vendor::*,
 };
 
+mod codes;
+mod config;
 mod datatypes;
+mod escrow;
+mod index;
 mod interface;
+mod registry;
+mod roles;
+mod storage;
 mod test;
 mod token;
 mod implementations;
\ No newline at end of file
@@ -0,0 +1,59 @@
+use crate::{
+    datatypes::{DataKey, FastBukaError, RiderProfile, RiderStatus, VendorProfile, VendorStatus},
+    storage::bump_persistent,
+};
+use soroban_sdk::{Address, Env};
+
+pub fn read_vendor(e: &Env, vendor: &Address) -> Option<VendorProfile> {
+    let key = DataKey::Vendor(vendor.clone());
+    bump_persistent(e, &key);
+    e.storage().persistent().get(&key)
+}
+
+pub fn write_vendor(e: &Env, vendor: &Address, profile: &VendorProfile) {
+    let key = DataKey::Vendor(vendor.clone());
+    e.storage().persistent().set(&key, profile);
+    bump_persistent(e, &key);
+}
+
+// Vendors must be registered and approved to receive or handle orders
+pub fn require_approved_vendor(e: &Env, vendor: &Address) -> Result<VendorProfile, FastBukaError> {
+    let profile = read_vendor(e, vendor).ok_or(FastBukaError::VendorNotRegistered)?;
+    if profile.status != VendorStatus::Approved {
+        return Err(FastBukaError::VendorNotApproved);
+    }
+    Ok(profile)
+}
+
+// Address vendor payouts are sent to
+pub fn vendor_payout_address(e: &Env, vendor: &Address) -> Address {
+    read_vendor(e, vendor)
+        .map(|profile| profile.payout_address)
+        .unwrap_or(vendor.clone())
+}
+
+pub fn read_rider(e: &Env, rider: &Address) -> Option<RiderProfile> {
+    let key = DataKey::Rider(rider.clone());
+    bump_persistent(e, &key);
+    e.storage().persistent().get(&key)
+}
+
+pub fn write_rider(e: &Env, rider: &Address, profile: &RiderProfile) {
+    let key = DataKey::Rider(rider.clone());
+    e.storage().persistent().set(&key, profile);
+    bump_persistent(e, &key);
+}
+
+// Riders must be registered, approved and online to take orders
+pub fn require_active_rider(e: &Env, rider: &Address) -> Result<RiderProfile, FastBukaError> {
+    let profile = read_rider(e, rider).ok_or(FastBukaError::RiderNotRegistered)?;
+    match profile.status {
+        RiderStatus::Pending => return Err(FastBukaError::RiderNotApproved),
+        RiderStatus::Suspended => return Err(FastBukaError::RiderSuspended),
+        RiderStatus::Approved => {}
+    }
+    if !profile.online {
+        return Err(FastBukaError::RiderOffline);
+    }
+    Ok(profile)
+}
@@ -0,0 +1,98 @@
+use crate::{
+    config::read_config,
+    datatypes::{DataKey, FastBukaError, Role},
+};
+use soroban_sdk::{Address, Env, Vec};
+
+// Every role, in the order they are granted to the deployer
+pub fn all_roles(e: &Env) -> Vec<Role> {
+    Vec::from_array(
+        e,
+        [
+            Role::SuperAdmin,
+            Role::Arbitrator,
+            Role::OnboardingManager,
+            Role::TreasuryManager,
+            Role::Pauser,
+            Role::AppealArbitrator,
+            Role::Dispatcher,
+        ],
+    )
+}
+
+pub fn read_role_members(e: &Env, role: Role) -> Vec<Address> {
+    e.storage()
+        .instance()
+        .get(&DataKey::Role(role))
+        .unwrap_or_else(|| Vec::new(e))
+}
+
+pub fn write_role_members(e: &Env, role: Role, members: &Vec<Address>) {
+    e.storage().instance().set(&DataKey::Role(role), members);
+}
+
+pub fn has_role(e: &Env, account: &Address, role: Role) -> bool {
+    read_role_members(e, role).contains(account)
+}
+
+pub fn require_role(e: &Env, account: &Address, role: Role) -> Result<(), FastBukaError> {
+    if !has_role(e, account, role) {
+        return Err(FastBukaError::MissingRole);
+    }
+    Ok(())
+}
+
+// Whether removing `account` would leave the contract without a super admin
+pub fn is_last_super_admin(e: &Env, account: &Address) -> bool {
+    let members = read_role_members(e, Role::SuperAdmin);
+    members.len() == 1 && members.contains(account)
+}
+
+// Whether some appeal arbitrator can always decide an appeal. Everyone who approved a resolution
+// is kept off its appeal, so an appeal arbitrator who isn't an arbitrator is needed, or more
+// appeal arbitrators than a resolution collects approvals. A lone admin holding both roles
+// would otherwise leave every appealed payout frozen
+pub fn appeals_decidable(e: &Env) -> bool {
+    let arbitrators = read_role_members(e, Role::Arbitrator);
+    let appeal_arbitrators = read_role_members(e, Role::AppealArbitrator);
+    let max_approvers = read_config(e).required_approvals.max(1);
+    appeal_arbitrators.len() > max_approvers
+        || appeal_arbitrators.iter().any(|member| !arbitrators.contains(&member))
+}
+
+// Super admin proposals waiting to be accepted, keyed by the proposed account
+pub fn read_pending_admin(e: &Env, new_admin: &Address) -> Option<Address> {
+    e.storage().instance().get(&DataKey::PendingAdmin(new_admin.clone()))
+}
+
+pub fn write_pending_admin(e: &Env, new_admin: &Address, proposer: &Address) {
+    e.storage().instance().set(&DataKey::PendingAdmin(new_admin.clone()), proposer);
+}
+
+pub fn remove_pending_admin(e: &Env, new_admin: &Address) {
+    e.storage().instance().remove(&DataKey::PendingAdmin(new_admin.clone()));
+}
+
+// Returns false if the account already had the role
+pub fn grant_role(e: &Env, account: &Address, role: Role) -> bool {
+    let mut members = read_role_members(e, role.clone());
+    if members.contains(account) {
+        return false;
+    }
+    members.push_back(account.clone());
+    write_role_members(e, role, &members);
+    true
+}
+
+// Returns false if the account did not have the role
+pub fn revoke_role(e: &Env, account: &Address, role: Role) -> bool {
+    let mut members = read_role_members(e, role.clone());
+    match members.first_index_of(account) {
+        Some(index) => {
+            members.remove(index);
+            write_role_members(e, role, &members);
+            true
+        }
+        None => false,
+    }
+}
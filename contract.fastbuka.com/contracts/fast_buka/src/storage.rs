@@ -0,0 +1,142 @@
+use crate::datatypes::{
+    DataKey, Dispute, LegacyOrder, Order, RejectionReason, ResolutionProposal, INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD, ORDER_BUMP_AMOUNT,
+    ORDER_LIFETIME_THRESHOLD, PERSISTENT_BUMP_AMOUNT, PERSISTENT_LIFETIME_THRESHOLD,
+};
+use soroban_sdk::{Env, Vec};
+
+// Config and admins live in instance storage and share the contract instance's TTL
+pub fn bump_instance(e: &Env) {
+    e.storage()
+        .instance()
+        .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+// Extend a long-lived persistent entry, if it exists
+pub fn bump_persistent(e: &Env, key: &DataKey) {
+    if e.storage().persistent().has(key) {
+        e.storage()
+            .persistent()
+            .extend_ttl(key, PERSISTENT_LIFETIME_THRESHOLD, PERSISTENT_BUMP_AMOUNT);
+    }
+}
+
+pub fn bump_order_entry(e: &Env, order_id: u128) {
+    e.storage()
+        .persistent()
+        .extend_ttl(&DataKey::Order(order_id), ORDER_LIFETIME_THRESHOLD, ORDER_BUMP_AMOUNT);
+}
+
+pub fn read_order(e: &Env, order_id: u128) -> Option<Order> {
+    let order = e.storage().persistent().get::<DataKey, Order>(&DataKey::Order(order_id));
+    if order.is_some() {
+        bump_order_entry(e, order_id);
+    }
+    order
+}
+
+pub fn write_order(e: &Env, order: &Order) {
+    e.storage().persistent().set(&DataKey::Order(order.id), order);
+    bump_order_entry(e, order.id);
+    bump_instance(e);
+}
+
+// Move an order stored under its bare id, as done before `DataKey::Order`, to its namespaced key.
+// An order already stored under that key is never overwritten; the legacy entry is left in place
+pub fn migrate_legacy_order(e: &Env, order_id: u128) -> Option<Order> {
+    if e.storage().persistent().has(&DataKey::Order(order_id)) {
+        return None;
+    }
+    let legacy = e.storage().persistent().get::<u128, LegacyOrder>(&order_id)?;
+    let order = upgrade_legacy_order(legacy);
+    e.storage().persistent().remove(&order_id);
+    write_order(e, &order);
+    Some(order)
+}
+
+// Legacy orders carried no platform fee, assignment or timestamps beyond creation. Their
+// plaintext confirmation number is dropped; a delivery code has to be committed afresh
+fn upgrade_legacy_order(legacy: LegacyOrder) -> Order {
+    Order {
+        id: legacy.id,
+        user: legacy.user,
+        token: legacy.token,
+        vendor: legacy.vendor,
+        amount: legacy.amount,
+        rider_fee: legacy.rider_fee,
+        platform_fee: 0,
+        status: legacy.status,
+        assigned_rider: legacy.rider.clone(),
+        rider: legacy.rider,
+        assigned_at: None,
+        assignment_accepted: false,
+        created_at: legacy.created_at,
+        ready_at: None,
+        delivered_at: None,
+        pickup_code_hash: None,
+        delivery_code_hash: None,
+        pickup_code_length: 0,
+        delivery_code_length: 0,
+        failed_delivery_attempts: 0,
+        delivery_locked_until: 0,
+        failed_pickup_attempts: 0,
+        pickup_locked_until: 0,
+    }
+}
+
+pub fn read_disputed_orders(e: &Env) -> Vec<u128> {
+    let key = DataKey::DisputedOrders;
+    bump_persistent(e, &key);
+    e.storage().persistent().get(&key).unwrap_or_else(|| Vec::new(e))
+}
+
+pub fn write_disputed_orders(e: &Env, disputed_orders: &Vec<u128>) {
+    let key = DataKey::DisputedOrders;
+    e.storage().persistent().set(&key, disputed_orders);
+    bump_persistent(e, &key);
+}
+
+pub fn read_dispute(e: &Env, order_id: u128) -> Option<Dispute> {
+    e.storage().persistent().get(&DataKey::Dispute(order_id))
+}
+
+pub fn write_dispute(e: &Env, dispute: &Dispute) {
+    let key = DataKey::Dispute(dispute.order_id);
+    e.storage().persistent().set(&key, dispute);
+    bump_order_record(e, &key);
+}
+
+pub fn read_rejection_reason(e: &Env, order_id: u128) -> Option<RejectionReason> {
+    e.storage().persistent().get(&DataKey::RejectionReason(order_id))
+}
+
+pub fn write_rejection_reason(e: &Env, order_id: u128, reason: &RejectionReason) {
+    let key = DataKey::RejectionReason(order_id);
+    e.storage().persistent().set(&key, reason);
+    bump_order_record(e, &key);
+}
+
+// Records kept alongside an order, such as its dispute, live as long as the order does
+pub fn bump_order_record(e: &Env, key: &DataKey) {
+    if e.storage().persistent().has(key) {
+        e.storage()
+            .persistent()
+            .extend_ttl(key, ORDER_LIFETIME_THRESHOLD, ORDER_BUMP_AMOUNT);
+    }
+}
+
+pub fn read_resolution_proposal(e: &Env, order_id: u128) -> Option<ResolutionProposal> {
+    e.storage().persistent().get(&DataKey::ResolutionProposal(order_id))
+}
+
+// Proposals are removed once executed, so the order TTL comfortably covers their expiry
+pub fn write_resolution_proposal(e: &Env, proposal: &ResolutionProposal) {
+    let key = DataKey::ResolutionProposal(proposal.order_id);
+    e.storage().persistent().set(&key, proposal);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, ORDER_LIFETIME_THRESHOLD, ORDER_BUMP_AMOUNT);
+}
+
+pub fn remove_resolution_proposal(e: &Env, order_id: u128) {
+    e.storage().persistent().remove(&DataKey::ResolutionProposal(order_id));
+}
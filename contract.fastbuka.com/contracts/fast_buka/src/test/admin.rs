@@ -3,12 +3,22 @@ use crate::{
     FastBukaContract,
     FastBukaContractClient,
     datatypes::OrderStatus,
-    datatypes::DisputeResolution,
+    datatypes::{DisputeOutcome, DisputeParty, DisputeResolution, DisputeSplit, Evidence},
+    datatypes::EscrowConfig,
+    datatypes::TokenConfig,
+    datatypes::FastBukaError,
+    datatypes::{DataKey, LegacyOrder, Order, OrderIndex, PauseGroup, Role, CONTRACT_VERSION},
 };
-use soroban_sdk::{testutils::Address as _, Address, Env, String};
-use super::common::{create_token};
+use soroban_sdk::{testutils::{Address as _, Ledger}, vec, Address, BytesN, Env, String, Vec};
+use super::common::{allow_token, commit_delivery_code, commit_pickup_code, create_token, onboard_rider, onboard_vendor, wait_out_response_window};
 extern crate std;
 
+// This contract built for wasm32v1-none, used as the upgrade target. Rebuild it with
+// `make fixtures` after changing the contract interface
+mod upgraded {
+    soroban_sdk::contractimport!(file = "src/test/fixtures/fast_buka_escrow.wasm");
+}
+
 
 
 #[test]
@@ -28,7 +38,8 @@ fn test_admin_management() {
 
     // Test adding new admin
     let admin2 = Address::generate(&env);
-    client.add_admin(&admin1, &admin2);
+    client.propose_admin(&admin1, &admin2);
+    client.accept_admin(&admin2);
 
     // Verify second admin was added
     let admins = client.get_admins();
@@ -57,7 +68,7 @@ fn test_non_admin_cannot_add_admin() {
     let client = FastBukaContractClient::new(&env, &contract_id);
 
     // Non-admin tries to add new admin
-    client.add_admin(&non_admin, &Address::generate(&env));
+    client.propose_admin(&non_admin, &Address::generate(&env));
 }
 
 #[test]
@@ -77,6 +88,49 @@ fn test_non_admin_cannot_remove_admin() {
 }
 
 
+#[test]
+fn test_two_step_admin_handoff() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    // The only super admin cannot be removed, by themselves or via revoke_role
+    let result = client.try_remove_admin(&admin, &admin);
+    assert_eq!(result, Err(Ok(FastBukaError::LastSuperAdmin)));
+    let result = client.try_revoke_role(&admin, &admin, &Role::SuperAdmin);
+    assert_eq!(result, Err(Ok(FastBukaError::LastSuperAdmin)));
+
+    // A proposal grants nothing until the proposed account accepts
+    let mistyped = Address::generate(&env);
+    client.propose_admin(&admin, &mistyped);
+    assert_eq!(client.get_pending_admin_proposer(&mistyped), Some(admin.clone()));
+    assert!(!client.has_role(&mistyped, &Role::SuperAdmin));
+    client.cancel_admin_proposal(&admin, &mistyped);
+    let result = client.try_accept_admin(&mistyped);
+    assert_eq!(result, Err(Ok(FastBukaError::ProposalNotFound)));
+
+    // Granting super admin also goes through the proposal
+    let successor = Address::generate(&env);
+    client.grant_role(&admin, &successor, &Role::SuperAdmin);
+    assert!(!client.has_role(&successor, &Role::SuperAdmin));
+    client.accept_admin(&successor);
+    assert!(client.has_role(&successor, &Role::SuperAdmin));
+    assert_eq!(client.get_pending_admin_proposer(&successor), None);
+
+    // Once the successor is in, the original admin can step down, giving up every role
+    client.remove_admin(&admin, &admin);
+    assert_eq!(client.get_admins(), vec![&env, successor.clone()]);
+    assert!(!client.has_role(&admin, &Role::Arbitrator));
+    assert!(!client.has_role(&admin, &Role::TreasuryManager));
+    assert!(!client.has_role(&admin, &Role::Dispatcher));
+    let result = client.try_remove_admin(&successor, &successor);
+    assert_eq!(result, Err(Ok(FastBukaError::LastSuperAdmin)));
+}
+
+
 #[test]
 fn test_get_all_disputed_orders() {
     let env = Env::default();
@@ -94,6 +148,9 @@ fn test_get_all_disputed_orders() {
     // Create token and setup
     let usdc_token = create_token(&env, &admin);
     let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
     let total_amount: i128 = 1000;
     let rider_fee: i128 = 100;
 
@@ -114,8 +171,11 @@ fn test_get_all_disputed_orders() {
 
     // Complete order flow for first order
     client.update_order_status(&order_id1, &vendor);
-    client.pickup_order(&order_id1, &rider);
-    client.rider_confirms_delivery(&order_id1);
+    client.assign_rider(&order_id1, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id1);
+    client.pickup_order(&order_id1, &rider, &pickup_code, &pickup_salt);
+    let (code, salt) = commit_delivery_code(&env, &client, &user, &order_id1);
+    client.rider_confirms_delivery(&order_id1, &rider, &code, &salt);
     
     // Create second order
     let order_id2 = client.create_order(&user, &token_address, &vendor, &total_amount, &rider_fee);
@@ -124,15 +184,18 @@ fn test_get_all_disputed_orders() {
 
     // Complete order flow for second order
     client.update_order_status(&order_id2, &vendor);
-    client.pickup_order(&order_id2, &rider);
-    client.rider_confirms_delivery(&order_id2);
+    client.assign_rider(&order_id2, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id2);
+    client.pickup_order(&order_id2, &rider, &pickup_code, &pickup_salt);
+    let (code, salt) = commit_delivery_code(&env, &client, &user, &order_id2);
+    client.rider_confirms_delivery(&order_id2, &rider, &code, &salt);
 
     // Raise disputes
     let reason = String::from_str(&env, "Test dispute");
-    client.customer_raise_dispute(&order_id1, &user, &reason.clone());
+    client.customer_raise_dispute(&order_id1, &user, &reason.clone(), &vec![&env]);
     std::println!("Dispute 1 raised");
 
-    client.customer_raise_dispute(&order_id2, &user, &reason);
+    client.customer_raise_dispute(&order_id2, &user, &reason, &vec![&env]);
     std::println!("Dispute 2 raised");
 
     // Get and verify disputed orders
@@ -155,7 +218,7 @@ fn test_cannot_add_duplicate_admin() {
     let client = FastBukaContractClient::new(&env, &contract_id);
 
     // Try to add same admin again
-    client.add_admin(&admin, &admin);
+    client.propose_admin(&admin, &admin);
 }
 
 #[test]
@@ -181,6 +244,9 @@ fn test_admin_settle_raised_dispute_by_customer() {
     // Create and setup token
     let usdc_token = create_token(&env, &admin);
     let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
     let total_amount: i128 = 1000;
     let rider_fee: i128 = 100;
 
@@ -208,19 +274,63 @@ fn test_admin_settle_raised_dispute_by_customer() {
 
     // Complete order flow
     client.update_order_status(&order_id, &vendor);
-    client.pickup_order(&order_id, &rider);
-    client.rider_confirms_delivery(&order_id);
+    client.assign_rider(&order_id, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+    client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
+    let (code, salt) = commit_delivery_code(&env, &client, &user, &order_id);
+    client.rider_confirms_delivery(&order_id, &rider, &code, &salt);
 
     // Raise dispute
     let reason = String::from_str(&env, "Issues with order");
-    client.customer_raise_dispute(&order_id, &user, &reason);
+    let evidence = Evidence {
+        hash: BytesN::from_array(&env, &[7; 32]),
+        uri: String::from_str(&env, "ipfs://photo-of-order"),
+    };
+    env.ledger().set_timestamp(1_000);
+    client.customer_raise_dispute(&order_id, &user, &reason, &vec![&env, evidence.clone()]);
 
     let order = client.get_order(&order_id);
     assert_eq!(order.status, OrderStatus::Disputed);
 
+    // The dispute is recorded for arbitrators
+    let dispute = client.get_dispute(&order_id);
+    assert_eq!(dispute.initiator, user);
+    assert_eq!(dispute.initiator_role, DisputeParty::Customer);
+    assert_eq!(dispute.reason, reason);
+    assert_eq!(dispute.evidence, vec![&env, evidence]);
+    assert_eq!(dispute.raised_at, 1_000);
+    assert_eq!(dispute.resolution, DisputeOutcome::Pending);
+    assert_eq!(dispute.resolver, None);
+
+    // The vendor and rider get to respond before anyone resolves it
+    let result = client.try_resolve_dispute(&order_id, &DisputeResolution::VendorFault, &admin);
+    assert_eq!(result, Err(Ok(FastBukaError::DeadlineNotReached)));
+
+    let statement = String::from_str(&env, "Order left the kitchen complete");
+    client.respond_to_dispute(&order_id, &vendor, &statement, &vec![&env]);
+    let result = client.try_respond_to_dispute(&order_id, &vendor, &statement, &vec![&env]);
+    assert_eq!(result, Err(Ok(FastBukaError::DuplicateSubmission)));
+    let result = client.try_respond_to_dispute(&order_id, &user, &statement, &vec![&env]);
+    assert_eq!(result, Err(Ok(FastBukaError::UnauthorizedAccess)));
+
+    let result = client.try_resolve_dispute(&order_id, &DisputeResolution::VendorFault, &admin);
+    assert_eq!(result, Err(Ok(FastBukaError::DeadlineNotReached)));
+    client.respond_to_dispute(&order_id, &rider, &String::from_str(&env, "Delivered as handed over"), &vec![&env]);
+
+    let dispute = client.get_dispute(&order_id);
+    assert_eq!(dispute.responses.len(), 2);
+    assert_eq!(dispute.responses.get(0).unwrap().party, DisputeParty::Vendor);
+    assert_eq!(dispute.responses.get(0).unwrap().statement, statement);
+
     // Resolve dispute as vendor fault
+    env.ledger().set_timestamp(2_000);
     client.resolve_dispute(&order_id, &DisputeResolution::VendorFault, &admin);
 
+    let dispute = client.get_dispute(&order_id);
+    assert_eq!(dispute.resolution, DisputeOutcome::Resolved(DisputeResolution::VendorFault));
+    assert_eq!(dispute.resolver, Some(admin.clone()));
+    assert_eq!(dispute.resolved_at, Some(2_000));
+
     // Check final balances
     let final_user_balance = usdc_token.balance(&user);
     let final_rider_balance = usdc_token.balance(&rider);
@@ -244,4 +354,937 @@ fn test_admin_settle_raised_dispute_by_customer() {
 
     let resolved_order = client.get_order(&order_id);
     assert_eq!(resolved_order.status, OrderStatus::Resolved);
-}
\ No newline at end of file
+}
+#[test]
+fn test_admin_set_config() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    // Three approvals need three arbitrators
+    let arbitrators = [Address::generate(&env), Address::generate(&env)];
+    let config = EscrowConfig {
+        waiting_timeout: 15 * 60,
+        pickup_timeout: 45 * 60,
+        confirmation_window: 12 * 60 * 60,
+        assignment_timeout: 5 * 60,
+        max_code_attempts: 5,
+        code_lockout: 60 * 60,
+        code_length: 8,
+        required_approvals: 3,
+        proposal_expiry: 24 * 60 * 60,
+        dispute_response_window: 12 * 60 * 60,
+        appeal_window: 6 * 60 * 60,
+        appeal_bond_bps: 1000,
+    };
+    let result = client.try_set_config(&admin, &config);
+    assert_eq!(result, Err(Ok(FastBukaError::InvalidConfig)));
+
+    for arbitrator in arbitrators.iter() {
+        client.grant_role(&admin, arbitrator, &Role::Arbitrator);
+    }
+    client.set_config(&admin, &config);
+
+    assert_eq!(client.get_config(), config);
+}
+
+#[test]
+fn test_set_config_rejects_invalid_code_length() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let result = client.try_set_config(&admin, &EscrowConfig { code_length: 3, required_approvals: 1, ..client.get_config() });
+    assert_eq!(result, Err(Ok(FastBukaError::InvalidConfig)));
+
+    let result = client.try_set_config(&admin, &EscrowConfig { code_length: 9, required_approvals: 1, ..client.get_config() });
+    assert_eq!(result, Err(Ok(FastBukaError::InvalidConfig)));
+}
+
+#[test]
+#[should_panic]
+fn test_non_admin_cannot_set_config() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let non_admin = Address::generate(&env);
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    client.set_config(&non_admin, &EscrowConfig { waiting_timeout: 60, ..client.get_config() });
+}
+
+#[test]
+fn test_token_platform_fee_overrides_default() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let usdc_token = create_token(&env, &admin);
+    let other_token = create_token(&env, &admin);
+
+    client.set_treasury(&admin, &treasury);
+    assert_eq!(client.get_treasury(), Some(treasury));
+
+    client.set_platform_fee(&admin, &None, &300);
+    client.set_platform_fee(&admin, &Some(usdc_token.address.clone()), &100);
+
+    assert_eq!(client.get_platform_fee(&usdc_token.address), 100);
+    assert_eq!(client.get_platform_fee(&other_token.address), 300);
+}
+
+#[test]
+#[should_panic]
+fn test_platform_fee_requires_treasury() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    // No treasury configured yet (should panic)
+    client.set_platform_fee(&admin, &None, &500);
+}
+
+#[test]
+fn test_resolve_dispute_collects_platform_fee() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    let rider = Address::generate(&env);
+    let treasury = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
+    let total_amount: i128 = 1000;
+    let rider_fee: i128 = 100;
+    usdc_token.mint(&user, &total_amount);
+
+    client.set_treasury(&admin, &treasury);
+    client.set_platform_fee(&admin, &None, &1000);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &total_amount, &rider_fee);
+    client.update_order_status(&order_id, &vendor);
+    client.assign_rider(&order_id, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+    client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
+    let (code, salt) = commit_delivery_code(&env, &client, &user, &order_id);
+    client.rider_confirms_delivery(&order_id, &rider, &code, &salt);
+    client.customer_raise_dispute(&order_id, &user, &String::from_str(&env, "Order was cold"), &vec![&env]);
+
+    // Nobody else responds
+    wait_out_response_window(&env, &client);
+
+    // Resolve dispute as rider fault
+    client.resolve_dispute(&order_id, &DisputeResolution::RiderFault, &admin);
+
+    // In RiderFault case:
+    // - Customer gets the rider fee back
+    // - Vendor gets the subtotal minus the platform fee
+    // - Treasury gets the platform fee
+    let platform_fee = (total_amount - rider_fee) / 10;
+    assert_eq!(usdc_token.balance(&user), rider_fee);
+    assert_eq!(usdc_token.balance(&vendor), total_amount - rider_fee - platform_fee);
+    assert_eq!(usdc_token.balance(&treasury), platform_fee);
+    assert_eq!(usdc_token.balance(&rider), 0);
+    assert_eq!(usdc_token.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_resolve_dispute_with_custom_split() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    let rider = Address::generate(&env);
+    let treasury = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
+    usdc_token.mint(&user, &1000);
+    client.set_treasury(&admin, &treasury);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &1000, &100);
+    client.update_order_status(&order_id, &vendor);
+    client.assign_rider(&order_id, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+    client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
+    let (code, salt) = commit_delivery_code(&env, &client, &user, &order_id);
+    client.rider_confirms_delivery(&order_id, &rider, &code, &salt);
+    client.customer_raise_dispute(&order_id, &user, &String::from_str(&env, "One item missing"), &vec![&env]);
+
+    // Nobody else responds
+    wait_out_response_window(&env, &client);
+    let statement = String::from_str(&env, "Everything was packed");
+    let result = client.try_respond_to_dispute(&order_id, &vendor, &statement, &vec![&env]);
+    assert_eq!(result, Err(Ok(FastBukaError::DeadlinePassed)));
+
+    // Shares that do not add up to the escrowed amount, or are negative, are refused
+    let split = |customer_amount, vendor_amount, rider_amount, platform_amount| {
+        DisputeResolution::Custom(DisputeSplit { customer_amount, vendor_amount, rider_amount, platform_amount })
+    };
+    let result = client.try_resolve_dispute(&order_id, &split(200, 700, 100, 10), &admin);
+    assert_eq!(result, Err(Ok(FastBukaError::InvalidSplit)));
+    let result = client.try_resolve_dispute(&order_id, &split(-100, 1000, 100, 0), &admin);
+    assert_eq!(result, Err(Ok(FastBukaError::InvalidSplit)));
+
+    // Refund the missing item and pay everyone else their share
+    client.resolve_dispute(&order_id, &split(200, 680, 100, 20), &admin);
+    assert_eq!(client.get_order(&order_id).status, OrderStatus::Resolved);
+    assert_eq!(usdc_token.balance(&user), 200);
+    assert_eq!(usdc_token.balance(&vendor), 680);
+    assert_eq!(usdc_token.balance(&rider), 100);
+    assert_eq!(usdc_token.balance(&treasury), 20);
+    assert_eq!(usdc_token.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_high_value_dispute_needs_arbitrator_approvals() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    let rider = Address::generate(&env);
+    let arbitrator = Address::generate(&env);
+    let other_arbitrator = Address::generate(&env);
+    client.grant_role(&admin, &arbitrator, &Role::Arbitrator);
+    client.grant_role(&admin, &other_arbitrator, &Role::Arbitrator);
+
+    // Orders above 500 need two arbitrators
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    let config = TokenConfig {
+        min_amount: 1,
+        max_amount: i128::MAX,
+        enabled: true,
+        approval_threshold: Some(500),
+        appeal_threshold: None,
+    };
+    client.add_accepted_token(&admin, &token_address, &config);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
+    usdc_token.mint(&user, &1000);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &1000, &100);
+    client.update_order_status(&order_id, &vendor);
+    client.assign_rider(&order_id, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+    client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
+    let (code, salt) = commit_delivery_code(&env, &client, &user, &order_id);
+    client.rider_confirms_delivery(&order_id, &rider, &code, &salt);
+    client.customer_raise_dispute(&order_id, &user, &String::from_str(&env, "Wrong order"), &vec![&env]);
+
+    // Nobody else responds
+    wait_out_response_window(&env, &client);
+
+    // The first arbitrator only proposes; nothing moves
+    client.resolve_dispute(&order_id, &DisputeResolution::VendorFault, &arbitrator);
+    let proposal = client.get_resolution_proposal(&order_id).unwrap();
+    assert_eq!(proposal.approvals, vec![&env, arbitrator.clone()]);
+    assert_eq!(proposal.required_approvals, 2);
+    assert_eq!(client.get_order(&order_id).status, OrderStatus::Disputed);
+    assert_eq!(usdc_token.balance(&contract_id), 1000);
+
+    // A competing proposal and a repeat approval are both refused
+    let result = client.try_resolve_dispute(&order_id, &DisputeResolution::CustomerFault, &other_arbitrator);
+    assert_eq!(result, Err(Ok(FastBukaError::ProposalPending)));
+    let result = client.try_approve_resolution(&order_id, &arbitrator);
+    assert_eq!(result, Err(Ok(FastBukaError::DuplicateSubmission)));
+
+    // Stale proposals cannot be approved and can be proposed again
+    env.ledger().set_timestamp(proposal.expires_at);
+    let result = client.try_approve_resolution(&order_id, &other_arbitrator);
+    assert_eq!(result, Err(Ok(FastBukaError::DeadlinePassed)));
+    client.resolve_dispute(&order_id, &DisputeResolution::VendorFault, &arbitrator);
+
+    // The proposer's approval lapses with their role, so one more approval is not enough
+    client.revoke_role(&admin, &arbitrator, &Role::Arbitrator);
+    client.approve_resolution(&order_id, &other_arbitrator);
+    assert_eq!(client.get_resolution_proposal(&order_id).unwrap().approvals, vec![&env, other_arbitrator.clone()]);
+    assert_eq!(client.get_order(&order_id).status, OrderStatus::Disputed);
+
+    // A second current arbitrator executes the resolution
+    client.approve_resolution(&order_id, &admin);
+    assert_eq!(client.get_order(&order_id).status, OrderStatus::Resolved);
+    assert_eq!(usdc_token.balance(&user), 900);
+    assert_eq!(usdc_token.balance(&rider), 100);
+    assert_eq!(client.get_resolution_proposal(&order_id), None);
+    let result = client.try_approve_resolution(&order_id, &other_arbitrator);
+    assert_eq!(result, Err(Ok(FastBukaError::ProposalNotFound)));
+}
+
+#[test]
+fn test_appeals_freeze_payout_until_decided() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    let rider = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let appeal_arbitrator = Address::generate(&env);
+
+    // Resolutions of orders above 500 can be appealed
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    let config = TokenConfig {
+        min_amount: 1,
+        max_amount: i128::MAX,
+        enabled: true,
+        approval_threshold: None,
+        appeal_threshold: Some(500),
+    };
+
+    // Upheld appeals pay the bond to the treasury, so one has to be set first
+    let result = client.try_add_accepted_token(&admin, &token_address, &config);
+    assert_eq!(result, Err(Ok(FastBukaError::InvalidConfig)));
+    client.set_treasury(&admin, &treasury);
+
+    // The deployer would approve its own resolutions, so someone else has to decide appeals
+    let result = client.try_add_accepted_token(&admin, &token_address, &config);
+    assert_eq!(result, Err(Ok(FastBukaError::InvalidConfig)));
+    client.grant_role(&admin, &appeal_arbitrator, &Role::AppealArbitrator);
+    client.add_accepted_token(&admin, &token_address, &config);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
+    usdc_token.mint(&user, &2100);
+    usdc_token.mint(&vendor, &50);
+
+    let disputed_order = || {
+        let order_id = client.create_order(&user, &token_address, &vendor, &1000, &100);
+        client.update_order_status(&order_id, &vendor);
+        client.assign_rider(&order_id, &vendor, &rider);
+        let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+        client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
+        let (code, salt) = commit_delivery_code(&env, &client, &user, &order_id);
+        client.rider_confirms_delivery(&order_id, &rider, &code, &salt);
+        client.customer_raise_dispute(&order_id, &user, &String::from_str(&env, "Food was cold"), &vec![&env]);
+        wait_out_response_window(&env, &client);
+        order_id
+    };
+
+    // The resolution is only provisional; nothing is paid out yet
+    let order_id = disputed_order();
+    client.resolve_dispute(&order_id, &DisputeResolution::VendorFault, &admin);
+    let dispute = client.get_dispute(&order_id);
+    assert_eq!(dispute.resolution, DisputeOutcome::Provisional(DisputeResolution::VendorFault));
+    assert_eq!(usdc_token.balance(&contract_id), 1000);
+    let result = client.try_resolve_dispute(&order_id, &DisputeResolution::CustomerFault, &admin);
+    assert_eq!(result, Err(Ok(FastBukaError::AlreadyResolved)));
+
+    // Only the vendor lost, so only the vendor can appeal; the bond is 5% of the order
+    let result = client.try_appeal_resolution(&order_id, &user);
+    assert_eq!(result, Err(Ok(FastBukaError::UnauthorizedAccess)));
+    client.appeal_resolution(&order_id, &vendor);
+    let dispute = client.get_dispute(&order_id);
+    assert_eq!(dispute.resolution, DisputeOutcome::Appealed(DisputeResolution::VendorFault));
+    assert_eq!(dispute.appeal_bond, 50);
+    assert_eq!(usdc_token.balance(&contract_id), 1050);
+
+    // Payout stays frozen past the appeal window, and the original resolver cannot decide the appeal
+    env.ledger().set_timestamp(dispute.appeal_deadline.unwrap());
+    let result = client.try_finalize_resolution(&order_id);
+    assert_eq!(result, Err(Ok(FastBukaError::NotAppealable)));
+    let result = client.try_decide_appeal(&order_id, &admin, &DisputeResolution::VendorFault);
+    assert_eq!(result, Err(Ok(FastBukaError::UnauthorizedAccess)));
+
+    // Confirming the resolution forfeits the bond to the treasury
+    client.decide_appeal(&order_id, &appeal_arbitrator, &DisputeResolution::VendorFault);
+    assert_eq!(client.get_order(&order_id).status, OrderStatus::Resolved);
+    assert_eq!(client.get_dispute(&order_id).resolution, DisputeOutcome::Resolved(DisputeResolution::VendorFault));
+    assert_eq!(usdc_token.balance(&treasury), 50);
+    assert_eq!(usdc_token.balance(&user), 1100 + 900);
+    assert_eq!(usdc_token.balance(&rider), 100);
+
+    // Overturning refunds the bond and pays out the new resolution
+    let order_id = disputed_order();
+    client.resolve_dispute(&order_id, &DisputeResolution::CustomerFault, &admin);
+    client.appeal_resolution(&order_id, &user);
+    assert_eq!(usdc_token.balance(&user), 1000 - 50);
+    client.decide_appeal(&order_id, &appeal_arbitrator, &DisputeResolution::VendorFault);
+    assert_eq!(usdc_token.balance(&user), 1000 + 900);
+    assert_eq!(usdc_token.balance(&treasury), 50);
+    assert_eq!(usdc_token.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_no_approving_arbitrator_can_decide_the_appeal() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    let rider = Address::generate(&env);
+    let proposer = Address::generate(&env);
+    let approver = Address::generate(&env);
+    for arbitrator in [&proposer, &approver] {
+        client.grant_role(&admin, arbitrator, &Role::Arbitrator);
+        client.grant_role(&admin, arbitrator, &Role::AppealArbitrator);
+    }
+
+    // Resolutions above 500 need two arbitrators and can be appealed
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    let config = TokenConfig {
+        min_amount: 1,
+        max_amount: i128::MAX,
+        enabled: true,
+        approval_threshold: Some(500),
+        appeal_threshold: Some(500),
+    };
+    client.set_treasury(&admin, &Address::generate(&env));
+    client.add_accepted_token(&admin, &token_address, &config);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
+    usdc_token.mint(&user, &1000);
+    usdc_token.mint(&vendor, &50);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &1000, &100);
+    client.update_order_status(&order_id, &vendor);
+    client.assign_rider(&order_id, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+    client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
+    let (code, salt) = commit_delivery_code(&env, &client, &user, &order_id);
+    client.rider_confirms_delivery(&order_id, &rider, &code, &salt);
+    client.customer_raise_dispute(&order_id, &user, &String::from_str(&env, "Food was cold"), &vec![&env]);
+    wait_out_response_window(&env, &client);
+
+    client.resolve_dispute(&order_id, &DisputeResolution::VendorFault, &proposer);
+    client.approve_resolution(&order_id, &approver);
+    let dispute = client.get_dispute(&order_id);
+    assert_eq!(dispute.resolver, Some(approver.clone()));
+    assert_eq!(dispute.approvers, vec![&env, proposer.clone(), approver.clone()]);
+    client.appeal_resolution(&order_id, &vendor);
+
+    // Neither the proposer nor the final approver can decide the appeal
+    let result = client.try_decide_appeal(&order_id, &proposer, &DisputeResolution::VendorFault);
+    assert_eq!(result, Err(Ok(FastBukaError::UnauthorizedAccess)));
+    let result = client.try_decide_appeal(&order_id, &approver, &DisputeResolution::VendorFault);
+    assert_eq!(result, Err(Ok(FastBukaError::UnauthorizedAccess)));
+
+    // An appeal arbitrator from outside the resolution can
+    client.decide_appeal(&order_id, &admin, &DisputeResolution::CustomerFault);
+    assert_eq!(client.get_order(&order_id).status, OrderStatus::Resolved);
+    assert_eq!(usdc_token.balance(&vendor), 50 + 900);
+}
+
+#[test]
+fn test_unappealed_resolution_pays_out_after_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    let rider = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    let config = TokenConfig {
+        min_amount: 1,
+        max_amount: i128::MAX,
+        enabled: true,
+        approval_threshold: None,
+        appeal_threshold: Some(0),
+    };
+    client.set_treasury(&admin, &Address::generate(&env));
+    client.grant_role(&admin, &Address::generate(&env), &Role::AppealArbitrator);
+    client.add_accepted_token(&admin, &token_address, &config);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
+    usdc_token.mint(&user, &1000);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &1000, &100);
+    client.update_order_status(&order_id, &vendor);
+    client.assign_rider(&order_id, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+    client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
+    let (code, salt) = commit_delivery_code(&env, &client, &user, &order_id);
+    client.rider_confirms_delivery(&order_id, &rider, &code, &salt);
+    client.customer_raise_dispute(&order_id, &user, &String::from_str(&env, "Late delivery"), &vec![&env]);
+    wait_out_response_window(&env, &client);
+
+    client.resolve_dispute(&order_id, &DisputeResolution::RiderFault, &admin);
+    let appeal_deadline = client.get_dispute(&order_id).appeal_deadline.unwrap();
+
+    // Nobody can finalize while the rider may still appeal
+    let result = client.try_finalize_resolution(&order_id);
+    assert_eq!(result, Err(Ok(FastBukaError::DeadlineNotReached)));
+
+    // Once the window closes anyone can release the payout; late appeals are refused
+    env.ledger().set_timestamp(appeal_deadline);
+    let result = client.try_appeal_resolution(&order_id, &rider);
+    assert_eq!(result, Err(Ok(FastBukaError::DeadlinePassed)));
+    client.finalize_resolution(&order_id);
+
+    let dispute = client.get_dispute(&order_id);
+    assert_eq!(dispute.resolution, DisputeOutcome::Resolved(DisputeResolution::RiderFault));
+    assert_eq!(dispute.resolver, Some(admin.clone()));
+    assert_eq!(usdc_token.balance(&user), 100);
+    assert_eq!(usdc_token.balance(&vendor), 900);
+    assert_eq!(client.get_order(&order_id).status, OrderStatus::Resolved);
+}
+
+#[test]
+fn test_admin_manage_accepted_tokens() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let usdc_token = create_token(&env, &admin);
+    let other_token = create_token(&env, &admin);
+
+    let config = TokenConfig {
+        min_amount: 10,
+        max_amount: 1_000_000,
+        enabled: true,
+        approval_threshold: None,
+        appeal_threshold: None,
+    };
+    client.add_accepted_token(&admin, &usdc_token.address, &config);
+    client.add_accepted_token(&admin, &other_token.address, &config);
+
+    let tokens = client.get_accepted_tokens();
+    assert_eq!(tokens.len(), 2);
+    assert_eq!(client.get_token_config(&usdc_token.address), Some(config));
+
+    // Remove one token
+    client.remove_accepted_token(&admin, &usdc_token.address);
+
+    let tokens = client.get_accepted_tokens();
+    assert_eq!(tokens.len(), 1);
+    assert!(tokens.contains(&other_token.address));
+    assert_eq!(client.get_token_config(&usdc_token.address), None);
+}
+
+#[test]
+#[should_panic]
+fn test_non_admin_cannot_add_accepted_token() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let non_admin = Address::generate(&env);
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let usdc_token = create_token(&env, &admin);
+    let config = TokenConfig {
+        min_amount: 1,
+        max_amount: 1000,
+        enabled: true,
+        approval_threshold: None,
+        appeal_threshold: None,
+    };
+    client.add_accepted_token(&non_admin, &usdc_token.address, &config);
+}
+
+#[test]
+fn test_migrate_legacy_order_keys() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    usdc_token.mint(&user, &3000);
+
+    for _ in 0..3 {
+        client.create_order(&user, &token_address, &vendor, &1000, &100);
+    }
+    client.update_order_status(&2, &vendor);
+
+    // Put the orders back under bare u128 keys in the old layout, without index entries, as
+    // older versions stored them
+    env.as_contract(&contract_id, || {
+        let storage = env.storage().persistent();
+        for order_id in 1..=3u128 {
+            let order: Order = storage.get(&DataKey::Order(order_id)).unwrap();
+            storage.remove(&DataKey::Order(order_id));
+            storage.set(&order_id, &LegacyOrder {
+                id: order.id,
+                user: order.user,
+                token: order.token,
+                vendor: order.vendor,
+                amount: order.amount,
+                rider_fee: order.rider_fee,
+                status: order.status,
+                rider: None,
+                created_at: order.created_at,
+                confirmation_number: Some(123_456),
+            });
+        }
+        storage.set(&DataKey::OrderCounter, &3u128);
+        storage.remove(&DataKey::OrderIndexPos(OrderIndex::Customer(user.clone()), 3));
+        storage.set(&DataKey::OrderIndexLen(OrderIndex::Customer(user.clone())), &2u32);
+    });
+    assert!(client.try_get_order(&1).is_err());
+
+    // Only admins can migrate
+    let non_admin = Address::generate(&env);
+    let result = client.try_migrate_order_keys(&non_admin, &1, &10);
+    assert_eq!(result, Err(Ok(FastBukaError::MissingRole)));
+
+    // Migrate in two batches
+    assert_eq!(client.migrate_order_keys(&admin, &1, &2), 2);
+    assert_eq!(client.migrate_order_keys(&admin, &3, &10), 1);
+    assert_eq!(client.migrate_order_keys(&admin, &1, &10), 0);
+
+    for order_id in 1..=3u128 {
+        let order = client.get_order(&order_id);
+        assert_eq!(order.id, order_id);
+        assert_eq!(order.amount, 1000);
+        assert_eq!(order.platform_fee, 0);
+        assert!(order.delivery_code_hash.is_none());
+    }
+    assert_eq!(client.get_order(&2).status, OrderStatus::ReadyForPickup);
+    env.as_contract(&contract_id, || {
+        assert!(!env.storage().persistent().has(&1u128));
+    });
+
+    // Migrated orders show up in the indexes
+    assert_eq!(client.get_customer_orders(&user, &0, &10).orders.len(), 3);
+    assert_eq!(client.get_orders_by_status(&OrderStatus::ReadyForPickup, &0, &10).orders.len(), 1);
+
+    // A legacy entry whose id is already taken never overwrites the stored order
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&2u128, &LegacyOrder {
+            id: 2,
+            user: user.clone(),
+            token: token_address.clone(),
+            vendor: vendor.clone(),
+            amount: 5,
+            rider_fee: 0,
+            status: OrderStatus::Waiting,
+            rider: None,
+            created_at: 0,
+            confirmation_number: None,
+        });
+    });
+    assert_eq!(client.migrate_order_keys(&admin, &1, &10), 0);
+    assert_eq!(client.get_order(&2).amount, 1000);
+    assert_eq!(client.get_order(&2).status, OrderStatus::ReadyForPickup);
+    env.as_contract(&contract_id, || {
+        assert!(env.storage().persistent().has(&2u128));
+    });
+}
+
+#[test]
+fn test_migrate_sets_current_version() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    assert_eq!(client.get_version(), CONTRACT_VERSION);
+
+    // Deployments from before versioning start at 0
+    env.as_contract(&contract_id, || env.storage().instance().remove(&DataKey::Version));
+    assert_eq!(client.get_version(), 0);
+
+    let non_admin = Address::generate(&env);
+    let result = client.try_migrate(&non_admin);
+    assert_eq!(result, Err(Ok(FastBukaError::MissingRole)));
+
+    assert_eq!(client.migrate(&admin), CONTRACT_VERSION);
+    assert_eq!(client.get_version(), CONTRACT_VERSION);
+
+    // Running it again is a no-op
+    assert_eq!(client.migrate(&admin), CONTRACT_VERSION);
+}
+
+#[test]
+fn test_roles_scope_admin_operations() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    // The deployer holds every role
+    assert!(client.has_role(&admin, &Role::SuperAdmin));
+    assert!(client.has_role(&admin, &Role::Arbitrator));
+    assert!(client.has_role(&admin, &Role::Pauser));
+
+    // Only super admins can hand out roles
+    let manager = Address::generate(&env);
+    let result = client.try_grant_role(&manager, &manager, &Role::OnboardingManager);
+    assert_eq!(result, Err(Ok(FastBukaError::MissingRole)));
+
+    client.grant_role(&admin, &manager, &Role::OnboardingManager);
+    assert!(client.has_role(&manager, &Role::OnboardingManager));
+    assert_eq!(client.get_role_members(&Role::OnboardingManager).len(), 2);
+
+    // An onboarding manager can approve vendors but nothing else
+    let vendor = Address::generate(&env);
+    onboard_vendor(&env, &client, &manager, &vendor);
+    let result = client.try_set_platform_fee(&manager, &None, &100);
+    assert_eq!(result, Err(Ok(FastBukaError::MissingRole)));
+    let result = client.try_set_paused(&manager, &PauseGroup::All, &true);
+    assert_eq!(result, Err(Ok(FastBukaError::MissingRole)));
+    let result = client.try_propose_admin(&manager, &manager);
+    assert_eq!(result, Err(Ok(FastBukaError::MissingRole)));
+
+    // Revoking the role takes the access away
+    client.revoke_role(&admin, &manager, &Role::OnboardingManager);
+    assert!(!client.has_role(&manager, &Role::OnboardingManager));
+    let result = client.try_suspend_vendor(&manager, &vendor);
+    assert_eq!(result, Err(Ok(FastBukaError::MissingRole)));
+}
+
+#[test]
+fn test_migrate_grants_roles_to_legacy_admins() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    // Rewind to a version 1 deployment that kept a flat admin list
+    let legacy_admin = Address::generate(&env);
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&DataKey::Admin, &vec![&env, legacy_admin.clone()]);
+        let storage = env.storage().instance();
+        for role in [Role::SuperAdmin, Role::Arbitrator, Role::OnboardingManager, Role::TreasuryManager, Role::Pauser, Role::AppealArbitrator] {
+            storage.remove(&DataKey::Role(role));
+        }
+        storage.set(&DataKey::Version, &1u32);
+    });
+    assert!(client.get_admins().is_empty());
+
+    let outsider = Address::generate(&env);
+    let result = client.try_migrate(&outsider);
+    assert_eq!(result, Err(Ok(FastBukaError::MissingRole)));
+
+    assert_eq!(client.migrate(&legacy_admin), CONTRACT_VERSION);
+
+    // Legacy admins keep every privilege and the flat list is gone
+    assert_eq!(client.get_admins(), vec![&env, legacy_admin.clone()]);
+    assert!(client.has_role(&legacy_admin, &Role::Arbitrator));
+    assert!(client.has_role(&legacy_admin, &Role::TreasuryManager));
+    assert!(client.has_role(&legacy_admin, &Role::Pauser));
+    env.as_contract(&contract_id, || assert!(!env.storage().persistent().has(&DataKey::Admin)));
+}
+
+#[test]
+fn test_migrate_moves_legacy_order_counter() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    usdc_token.mint(&user, &1000);
+
+    // Rewind to a version 1 deployment that counted orders in persistent storage
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&DataKey::OrderCounter, &5u128);
+        env.storage().instance().remove(&DataKey::OrderCounter);
+        env.storage().instance().set(&DataKey::Version, &1u32);
+    });
+
+    client.migrate(&admin);
+    env.as_contract(&contract_id, || {
+        assert_eq!(env.storage().instance().get::<_, u128>(&DataKey::OrderCounter), Some(5));
+    });
+
+    // New orders carry on from the legacy count instead of reusing its ids
+    assert_eq!(client.create_order(&user, &token_address, &vendor, &1000, &100), 6);
+}
+
+#[test]
+fn test_migrate_from_unversioned_layout() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let legacy_admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    let rider = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+
+    // Rewind to the unversioned layout: admins, the order counter and orders under bare u128
+    // keys all in persistent storage, with the two escrows already funded
+    env.as_contract(&contract_id, || {
+        let instance = env.storage().instance();
+        instance.remove(&DataKey::Version);
+        instance.remove(&DataKey::OrderCounter);
+        for role in [Role::SuperAdmin, Role::Arbitrator, Role::OnboardingManager, Role::TreasuryManager, Role::Pauser, Role::AppealArbitrator, Role::Dispatcher] {
+            instance.remove(&DataKey::Role(role));
+        }
+
+        let persistent = env.storage().persistent();
+        persistent.set(&DataKey::Admin, &vec![&env, legacy_admin.clone()]);
+        persistent.set(&DataKey::OrderCounter, &2u128);
+        for (order_id, status, order_rider) in [(1u128, OrderStatus::Waiting, None), (2u128, OrderStatus::Delivered, Some(rider.clone()))] {
+            persistent.set(&order_id, &LegacyOrder {
+                id: order_id,
+                user: user.clone(),
+                token: token_address.clone(),
+                vendor: vendor.clone(),
+                amount: 1000,
+                rider_fee: 100,
+                status,
+                rider: order_rider,
+                created_at: 0,
+                confirmation_number: Some(4321),
+            });
+        }
+    });
+    usdc_token.mint(&contract_id, &2000);
+    assert_eq!(client.get_version(), 0);
+
+    // Only the legacy admins can run the migration
+    let result = client.try_migrate(&admin);
+    assert_eq!(result, Err(Ok(FastBukaError::MissingRole)));
+    assert_eq!(client.migrate(&legacy_admin), CONTRACT_VERSION);
+    assert!(client.has_role(&legacy_admin, &Role::SuperAdmin));
+    assert!(client.has_role(&legacy_admin, &Role::Arbitrator));
+    env.as_contract(&contract_id, || {
+        assert!(!env.storage().persistent().has(&DataKey::Admin));
+        assert_eq!(env.storage().instance().get::<_, u128>(&DataKey::OrderCounter), Some(2));
+    });
+
+    // Both legacy orders come across and settle from the existing escrow
+    assert_eq!(client.migrate_order_keys(&legacy_admin, &1, &10), 2);
+    assert_eq!(client.get_order(&1).status, OrderStatus::Waiting);
+    assert_eq!(client.get_order(&2).rider, Some(rider.clone()));
+    client.cancel_order(&1, &user);
+    client.user_confirms_order(&2, &user);
+    assert_eq!(usdc_token.balance(&user), 1000);
+    assert_eq!(usdc_token.balance(&vendor), 900);
+    assert_eq!(usdc_token.balance(&rider), 100);
+    assert_eq!(usdc_token.balance(&contract_id), 0);
+
+    // New orders don't reuse legacy ids
+    usdc_token.mint(&user, &1000);
+    assert_eq!(client.create_order(&user, &token_address, &vendor, &1000, &100), 3);
+}
+
+#[test]
+fn test_upgrade_keeps_orders_and_funds() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    usdc_token.mint(&user, &1000);
+    let order_id = client.create_order(&user, &token_address, &vendor, &1000, &100);
+
+    // Pretend the stored data predates the current version, so migrate has work to do
+    env.as_contract(&contract_id, || env.storage().instance().set(&DataKey::Version, &1u32));
+
+    let new_wasm_hash = env.deployer().upload_contract_wasm(upgraded::WASM);
+
+    // Only admins can upgrade
+    let non_admin = Address::generate(&env);
+    let result = client.try_upgrade(&non_admin, &new_wasm_hash);
+    assert_eq!(result, Err(Ok(FastBukaError::MissingRole)));
+
+    client.upgrade(&admin, &new_wasm_hash);
+
+    // The uploaded build reads the stored order and migrates the data
+    let upgraded_client = upgraded::Client::new(&env, &contract_id);
+    let order = upgraded_client.get_order(&order_id);
+    assert_eq!(order.amount, 1000);
+    assert_eq!(order.status, upgraded::OrderStatus::Waiting);
+    assert_eq!(upgraded_client.migrate(&admin), CONTRACT_VERSION);
+    assert_eq!(upgraded_client.get_version(), CONTRACT_VERSION);
+
+    // Stored orders, admins and escrowed funds are untouched
+    env.as_contract(&contract_id, || {
+        let order: Order = env.storage().persistent().get(&DataKey::Order(order_id)).unwrap();
+        assert_eq!(order.amount, 1000);
+        assert_eq!(order.status, OrderStatus::Waiting);
+
+        let admins: Vec<Address> = env.storage().instance().get(&DataKey::Role(Role::SuperAdmin)).unwrap();
+        assert!(admins.contains(&admin));
+        assert_eq!(env.storage().instance().get::<DataKey, u32>(&DataKey::Version), Some(CONTRACT_VERSION));
+    });
+    assert_eq!(usdc_token.balance(&contract_id), 1000);
+}
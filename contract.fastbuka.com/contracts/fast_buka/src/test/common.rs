@@ -1,15 +1,82 @@
 #![cfg(test)]
 
 use soroban_sdk::{
+    testutils::Ledger,
     Address, 
+    Bytes,
+    BytesN,
     Env, 
     IntoVal,
+    String,
 };
 use crate::token::token::{Token, TokenClient};
+use crate::{datatypes::{TokenConfig, VehicleType}, FastBukaContractClient};
 
 // Helper function to create test token
 pub fn create_token<'a>(e: &Env, admin: &Address) -> TokenClient<'a> {
     let token = TokenClient::new(e, &e.register(Token {}, ()));
     token.initialize(admin, &7, &"USDC".into_val(e), &"USDC".into_val(e));
     token
-}
\ No newline at end of file
+}
+
+// Helper function to accept a test token for orders
+pub fn allow_token(client: &FastBukaContractClient, admin: &Address, token: &Address) {
+    let config = TokenConfig {
+        min_amount: 1,
+        max_amount: i128::MAX,
+        enabled: true,
+        approval_threshold: None,
+        appeal_threshold: None,
+    };
+    client.add_accepted_token(admin, token, &config);
+}
+
+// Helper function to register and approve a test vendor
+pub fn onboard_vendor(env: &Env, client: &FastBukaContractClient, admin: &Address, vendor: &Address) {
+    let name = String::from_str(env, "Test Kitchen");
+    let metadata_uri = String::from_str(env, "ipfs://vendor");
+    client.register_vendor(vendor, &name, vendor, &metadata_uri);
+    client.approve_vendor(admin, vendor);
+}
+
+// Helper function to register, approve and bring a test rider online
+pub fn onboard_rider(env: &Env, client: &FastBukaContractClient, admin: &Address, rider: &Address) {
+    let metadata_uri = String::from_str(env, "ipfs://rider");
+    client.register_rider(rider, &VehicleType::Motorcycle, &metadata_uri);
+    client.approve_rider(admin, rider);
+    client.set_rider_online(rider, &true);
+}
+
+// Code and salt a client app would pick for an order, with the hash it commits on-chain
+pub fn handoff_code(env: &Env, client: &FastBukaContractClient, order_id: &u128, tag: u8) -> (u32, Bytes, BytesN<32>) {
+    let length = client.get_config().code_length;
+    let floor = 10u32.pow(length - 1);
+    let code = floor + ((*order_id as u32) * 7919 + tag as u32) % (9 * floor);
+    let mut salt = Bytes::from_array(env, &order_id.to_be_bytes());
+    salt.push_back(tag);
+    let mut preimage = salt.clone();
+    preimage.extend_from_array(&code.to_be_bytes());
+    (code, salt, env.crypto().sha256(&preimage).to_bytes())
+}
+
+// Helper function to commit a delivery code as the customer, returning the code and salt
+// the customer hands the rider
+pub fn commit_delivery_code(env: &Env, client: &FastBukaContractClient, customer: &Address, order_id: &u128) -> (u32, Bytes) {
+    let (code, salt, hash) = handoff_code(env, client, order_id, 1);
+    client.set_delivery_code(customer, order_id, &hash);
+    (code, salt)
+}
+
+// Helper function to commit a pickup code as the vendor, returning the code and salt
+// the vendor hands the rider
+pub fn commit_pickup_code(env: &Env, client: &FastBukaContractClient, vendor: &Address, order_id: &u128) -> (u32, Bytes) {
+    let (code, salt, hash) = handoff_code(env, client, order_id, 0);
+    client.set_pickup_code(vendor, order_id, &hash);
+    (code, salt)
+}
+
+// Helper function to move time past a dispute's response window, for when the other parties never respond
+pub fn wait_out_response_window(env: &Env, client: &FastBukaContractClient) {
+    let window = client.get_config().dispute_response_window;
+    env.ledger().set_timestamp(env.ledger().timestamp() + window);
+}
@@ -2,10 +2,10 @@
 use crate::{
     FastBukaContract,
     FastBukaContractClient,
-    datatypes::OrderStatus
+    datatypes::{DisputeResolution, EscrowConfig, FastBukaError, OrderStatus}
 };
-use soroban_sdk::{testutils::Address as _, Address, Env, String};
-use super::common::{create_token};
+use soroban_sdk::{testutils::{Address as _, Ledger}, vec, Address, Bytes, Env, String};
+use super::common::{allow_token, commit_delivery_code, commit_pickup_code, create_token, handoff_code, onboard_rider, onboard_vendor, wait_out_response_window};
 extern crate std;
 
 
@@ -28,6 +28,9 @@ fn test_customer_complete_order() {
     // Create and setup token
     let usdc_token = create_token(&env, &admin);
     let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
     let total_amount: i128 = 1000;
     let rider_fee: i128 = 100;
 
@@ -41,8 +44,11 @@ fn test_customer_complete_order() {
     
     // Get order through stages
     client.update_order_status(&order_id, &vendor);
-    client.pickup_order(&order_id, &rider);
-    client.rider_confirms_delivery(&order_id);
+    client.assign_rider(&order_id, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+    client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
+    let (code, salt) = commit_delivery_code(&env, &client, &user, &order_id);
+    client.rider_confirms_delivery(&order_id, &rider, &code, &salt);
     
     // Verify order is in Delivered state
     let order = client.get_order(&order_id);
@@ -87,6 +93,9 @@ fn test_check_order_status_by_user() {
     // Create and setup token
     let usdc_token = create_token(&env, &admin);
     let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
     let total_amount: i128 = 1000;
     let rider_fee: i128 = 100;
     usdc_token.mint(&user, &total_amount);
@@ -101,8 +110,7 @@ fn test_check_order_status_by_user() {
     assert_eq!(order.status, OrderStatus::Waiting);
 
     // Update order status with vendor
-    let confirmation = client.update_order_status(&order_id, &vendor);
-    std::println!("Confirmation code: {:?}", confirmation);
+    client.update_order_status(&order_id, &vendor);
     
      // Test2: Check status vendor done with food
      let __status = client.check_order_status(&user, &order_id);
@@ -113,19 +121,20 @@ fn test_check_order_status_by_user() {
     
 
     // Test pickup_order
-    client.pickup_order(&order_id, &rider);
+    client.assign_rider(&order_id, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+    client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
     let order = client.get_order(&order_id);
-    let rider_confirm_no = client.get_confirmation_number_rider(&order_id);
-    std::println!("rider_confirm_no: {}", rider_confirm_no);
 
    
     // Test3: Check status Rider picked up food
      client.check_order_status(&user, &order_id);
     assert_eq!(order.status, OrderStatus::PickedUp);
-    assert_eq!(order.rider, Some(rider));
+    assert_eq!(order.rider, Some(rider.clone()));
     
     // Test rider_confirms_delivery
-    client.rider_confirms_delivery(&order_id);
+    let (code, salt) = commit_delivery_code(&env, &client, &user, &order_id);
+    client.rider_confirms_delivery(&order_id, &rider, &code, &salt);
     let order = client.get_order(&order_id);
 
     // Test4: Check status Rider delivered food
@@ -166,6 +175,9 @@ fn test_raise_dispute_when_order_not_delivered() {
     // Create and setup token
     let usdc_token = create_token(&env, &admin);
     let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
     let total_amount: i128 = 1000;
     let rider_fee: i128 = 100;
     usdc_token.mint(&user, &total_amount);
@@ -176,25 +188,26 @@ fn test_raise_dispute_when_order_not_delivered() {
     std::println!("\nOrder created with ID: {}", order_id);
 
     // Update order status with vendor
-    let confirmation = client.update_order_status(&order_id, &vendor);
-    std::println!("Confirmation code: {:?}", confirmation);
+    client.update_order_status(&order_id, &vendor);
 
     
 
     // Test pickup_order
-    client.pickup_order(&order_id, &rider);
+    client.assign_rider(&order_id, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+    client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
     client.get_order(&order_id);
    
     // Test user confirms delivery
-    client.customer_raise_dispute(&order_id, &user, &reason);
+    client.customer_raise_dispute(&order_id, &user, &reason, &vec![&env]);
 
     // Test raise dispute with wrong address
-    client.customer_raise_dispute(&order_id, &vendor, &reason);
+    client.customer_raise_dispute(&order_id, &vendor, &reason, &vec![&env]);
 }
 
 
 #[test]
-fn test_get_confirmation_number_customer() {
+fn test_set_delivery_code() {
     let env = Env::default();
     env.mock_all_auths();
 
@@ -215,6 +228,8 @@ fn test_get_confirmation_number_customer() {
     // Create and setup token
     let usdc_token = create_token(&env, &admin);
     let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
     let total_amount: i128 = 1000;
     let rider_fee: i128 = 100;
     usdc_token.mint(&user, &total_amount);
@@ -224,10 +239,273 @@ fn test_get_confirmation_number_customer() {
     std::println!("\nOrder created with ID: {}", order_id);
 
     // Update order status with vendor to ReadyForPickup
-    let confirmation = client.update_order_status(&order_id, &vendor);
-    std::println!("Confirmation code: {:?}", confirmation);
+    client.update_order_status(&order_id, &vendor);
+
+    // Test 1: Should fail - only the order's customer can set the code
+    let (_, _, code_hash) = handoff_code(&env, &client, &order_id, 1);
+    let result = client.try_set_delivery_code(&vendor, &order_id, &code_hash);
+    assert_eq!(result, Err(Ok(FastBukaError::UnauthorizedAccess)));
 
     // Test 2: Should succeed - correct user and order is ready
-    let customer_confirmation = client.get_confirmation_number_customer(&user, &order_id);
-    assert_eq!(customer_confirmation, confirmation);
-}
\ No newline at end of file
+    client.set_delivery_code(&user, &order_id, &code_hash);
+
+    // Only the committed hash is kept on the order
+    let order = client.get_order(&order_id);
+    assert_eq!(order.delivery_code_hash, Some(code_hash.into()));
+}
+
+
+#[test]
+fn test_recommitting_delivery_code_replaces_it() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    let rider = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
+    usdc_token.mint(&user, &2000);
+
+    client.set_config(&admin, &EscrowConfig { code_length: 8, required_approvals: 1, ..client.get_config() });
+
+    // Two orders readied in the same ledger
+    let order_1 = client.create_order(&user, &token_address, &vendor, &1000, &100);
+    let order_2 = client.create_order(&user, &token_address, &vendor, &1000, &100);
+    client.update_order_status(&order_1, &vendor);
+    client.update_order_status(&order_2, &vendor);
+
+    let (code_1, salt_1) = commit_delivery_code(&env, &client, &user, &order_1);
+    assert!((10_000_000..=99_999_999).contains(&code_1));
+
+    // Codes of any other length are refused even when they match the hash
+    let short_salt = Bytes::from_array(&env, &[7; 32]);
+    let mut preimage = short_salt.clone();
+    preimage.extend_from_array(&123_456u32.to_be_bytes());
+    client.set_delivery_code(&user, &order_2, &env.crypto().sha256(&preimage).to_bytes());
+    client.assign_rider(&order_2, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_2);
+    client.pickup_order(&order_2, &rider, &pickup_code, &pickup_salt);
+    assert!(!client.rider_confirms_delivery(&order_2, &rider, &123_456, &short_salt));
+
+    // Committing again replaces the code, so the earlier one no longer delivers
+    client.assign_rider(&order_1, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_1);
+    client.pickup_order(&order_1, &rider, &pickup_code, &pickup_salt);
+    let (new_code, new_salt, new_hash) = handoff_code(&env, &client, &order_1, 2);
+    client.set_delivery_code(&user, &order_1, &new_hash);
+    assert_ne!(new_code, code_1);
+    assert!(!client.rider_confirms_delivery(&order_1, &rider, &code_1, &salt_1));
+    assert!(client.rider_confirms_delivery(&order_1, &rider, &new_code, &new_salt));
+}
+
+#[test]
+fn test_code_length_change_keeps_committed_codes() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    let rider = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
+    usdc_token.mint(&user, &1000);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &1000, &100);
+    client.update_order_status(&order_id, &vendor);
+    client.assign_rider(&order_id, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+    let (code, salt) = commit_delivery_code(&env, &client, &user, &order_id);
+
+    // Codes committed before the change are still checked against their original length
+    client.set_config(&admin, &EscrowConfig { code_length: 8, required_approvals: 1, ..client.get_config() });
+    assert!(client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt));
+    assert!(client.rider_confirms_delivery(&order_id, &rider, &code, &salt));
+    assert_eq!(client.get_order(&order_id).status, OrderStatus::Delivered);
+}
+
+#[test]
+#[should_panic]
+fn test_raise_dispute_after_confirmation_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    let rider = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
+    let total_amount: i128 = 1000;
+    let rider_fee: i128 = 100;
+    usdc_token.mint(&user, &total_amount);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &total_amount, &rider_fee);
+    client.update_order_status(&order_id, &vendor);
+    client.assign_rider(&order_id, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+    client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
+    let (code, salt) = commit_delivery_code(&env, &client, &user, &order_id);
+    client.rider_confirms_delivery(&order_id, &rider, &code, &salt);
+
+    let config = client.get_config();
+    env.ledger().set_timestamp(env.ledger().timestamp() + config.confirmation_window);
+
+    // Confirmation window has closed (should panic)
+    client.customer_raise_dispute(&order_id, &user, &String::from_str(&env, "Too late"), &vec![&env]);
+}
+
+#[test]
+fn test_customer_disputes_undelivered_order() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    let rider = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
+    let total_amount: i128 = 1000;
+    let rider_fee: i128 = 100;
+    usdc_token.mint(&user, &total_amount);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &total_amount, &rider_fee);
+    client.update_order_status(&order_id, &vendor);
+    client.assign_rider(&order_id, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+    client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
+
+    // The rider never shows up at the door
+    client.customer_raise_dispute(&order_id, &user, &String::from_str(&env, "Food never arrived"), &vec![&env]);
+    assert_eq!(client.get_order(&order_id).status, OrderStatus::Disputed);
+
+    wait_out_response_window(&env, &client);
+    client.resolve_dispute(&order_id, &DisputeResolution::RiderFault, &admin);
+
+    // The vendor is paid for the food and the delivery fee goes back to the customer
+    assert_eq!(usdc_token.balance(&user), rider_fee);
+    assert_eq!(usdc_token.balance(&vendor), total_amount - rider_fee);
+    assert_eq!(usdc_token.balance(&rider), 0);
+    assert_eq!(usdc_token.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_customer_complete_order_with_platform_fee() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    let rider = Address::generate(&env);
+    let treasury = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
+    let total_amount: i128 = 1000;
+    let rider_fee: i128 = 100;
+    usdc_token.mint(&user, &total_amount);
+
+    // 5% commission on the subtotal
+    client.set_treasury(&admin, &treasury);
+    client.set_platform_fee(&admin, &None, &500);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &total_amount, &rider_fee);
+    let expected_fee = (total_amount - rider_fee) * 500 / 10_000;
+    assert_eq!(client.get_order(&order_id).platform_fee, expected_fee);
+
+    client.update_order_status(&order_id, &vendor);
+    client.assign_rider(&order_id, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+    client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
+    let (code, salt) = commit_delivery_code(&env, &client, &user, &order_id);
+    client.rider_confirms_delivery(&order_id, &rider, &code, &salt);
+    client.user_confirms_order(&order_id, &user);
+
+    assert_eq!(usdc_token.balance(&vendor), total_amount - rider_fee - expected_fee);
+    assert_eq!(usdc_token.balance(&rider), rider_fee);
+    assert_eq!(usdc_token.balance(&treasury), expected_fee);
+    assert_eq!(usdc_token.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_vendor_paid_to_payout_address() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    let payout_address = Address::generate(&env);
+    let rider = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    let total_amount: i128 = 1000;
+    let rider_fee: i128 = 100;
+    usdc_token.mint(&user, &total_amount);
+
+    // Vendor settles to a separate payout account
+    client.register_vendor(&vendor, &String::from_str(&env, "Suya Spot"), &payout_address, &String::from_str(&env, ""));
+    client.approve_vendor(&admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &total_amount, &rider_fee);
+    client.update_order_status(&order_id, &vendor);
+    client.assign_rider(&order_id, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+    client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
+    let (code, salt) = commit_delivery_code(&env, &client, &user, &order_id);
+    client.rider_confirms_delivery(&order_id, &rider, &code, &salt);
+    client.user_confirms_order(&order_id, &user);
+
+    assert_eq!(usdc_token.balance(&payout_address), total_amount - rider_fee);
+    assert_eq!(usdc_token.balance(&vendor), 0);
+}
@@ -1,10 +1,17 @@
 #![cfg(test)]
 use crate::{
     FastBukaContract,
-    FastBukaContractClient
+    FastBukaContractClient,
+    datatypes::{
+        DataKey, EscrowConfig, Role, FastBukaError, OrderStatus, PauseGroup, RejectionReason, TokenConfig, VehicleType, DAY_IN_LEDGERS,
+        INSTANCE_BUMP_AMOUNT, ORDER_BUMP_AMOUNT,
+    },
 };
-use soroban_sdk::{testutils::Address as _, Address, Env};
-use super::common::{create_token};
+use soroban_sdk::{
+    testutils::{storage::{Instance as _, Persistent as _}, Address as _, Ledger},
+    vec, Address, Env, String,
+};
+use super::common::{allow_token, commit_delivery_code, commit_pickup_code, create_token, handoff_code, onboard_rider, onboard_vendor};
 extern crate std;
 
 
@@ -31,6 +38,8 @@ fn test_create_order_and_get_order() {
 
    let usdc_token = create_token(&env, &admin);
    let token_address = usdc_token.address.clone();
+   allow_token(&client, &admin, &token_address);
+   onboard_vendor(&env, &client, &admin, &vendor);
    usdc_token.mint(&user, &total_amount);
 
    std::println!("\nBalances before order:");
@@ -76,6 +85,8 @@ fn test_get_all_orders() {
 
    let usdc_token = create_token(&env, &admin);
    let token_address = usdc_token.address.clone();
+   allow_token(&client, &admin, &token_address);
+   onboard_vendor(&env, &client, &admin, &vendor);
 
    let amount1: i128 = 1000;
    let amount2: i128 = 2000;
@@ -86,7 +97,9 @@ fn test_get_all_orders() {
    let order2_id = client.create_order(&user2, &token_address, &vendor, &amount2, &200);
    std::println!("Created orders: {} and {}", order1_id, order2_id);
 
-   let orders = client.get_all_orders();
+   let page = client.get_all_orders(&0, &10);
+   assert_eq!(page.next_cursor, None);
+   let orders = page.orders;
    std::println!("\nRetrieved {} orders", orders.len());
    for (i, order) in orders.iter().enumerate() {
        std::println!("Order {}: Amount = {}", i+1, order.amount);
@@ -119,6 +132,9 @@ fn test_wrong_customer_complete_order() {
     
     let usdc_token = create_token(&env, &admin);
     let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
     let total_amount: i128 = 1000;
     let rider_fee: i128 = 100;
 
@@ -128,9 +144,603 @@ fn test_wrong_customer_complete_order() {
     
     // Get order to delivered state
     client.update_order_status(&order_id, &vendor);
-    client.pickup_order(&order_id, &rider);
-    client.rider_confirms_delivery(&order_id);
+    client.assign_rider(&order_id, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+    client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
+    let (code, salt) = commit_delivery_code(&env, &client, &user, &order_id);
+    client.rider_confirms_delivery(&order_id, &rider, &code, &salt);
     
     // Try to complete with wrong customer (should panic)
     client.user_confirms_order(&order_id, &wrong_user);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_customer_cancel_waiting_order() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    let total_amount: i128 = 1000;
+    let rider_fee: i128 = 100;
+
+    usdc_token.mint(&user, &total_amount);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &total_amount, &rider_fee);
+    assert_eq!(usdc_token.balance(&contract_id), total_amount);
+
+    // Customer cancels before the vendor accepts the order
+    client.cancel_order(&order_id, &user);
+
+    let order = client.get_order(&order_id);
+    assert_eq!(order.status, OrderStatus::Cancelled);
+
+    // Full amount is refunded from escrow
+    assert_eq!(usdc_token.balance(&user), total_amount);
+    assert_eq!(usdc_token.balance(&contract_id), 0);
+}
+
+#[test]
+#[should_panic]
+fn test_cannot_cancel_order_ready_for_pickup() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    let total_amount: i128 = 1000;
+    let rider_fee: i128 = 100;
+
+    usdc_token.mint(&user, &total_amount);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &total_amount, &rider_fee);
+    client.update_order_status(&order_id, &vendor);
+
+    // Vendor already accepted the order (should panic)
+    client.cancel_order(&order_id, &user);
+}
+
+#[test]
+#[should_panic]
+fn test_vendor_cannot_update_cancelled_order() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    let total_amount: i128 = 1000;
+    let rider_fee: i128 = 100;
+
+    usdc_token.mint(&user, &total_amount);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &total_amount, &rider_fee);
+    client.cancel_order(&order_id, &user);
+
+    // Cancelled orders cannot be moved back into the delivery flow (should panic)
+    client.update_order_status(&order_id, &vendor);
+}
+
+#[test]
+fn test_expire_stale_waiting_order() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    let total_amount: i128 = 1000;
+    let rider_fee: i128 = 100;
+
+    usdc_token.mint(&user, &total_amount);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &total_amount, &rider_fee);
+
+    // Vendor never accepts the order
+    let config = client.get_config();
+    env.ledger().set_timestamp(env.ledger().timestamp() + config.waiting_timeout);
+
+    // Anyone can expire it once the deadline has passed
+    client.expire_order(&order_id);
+
+    let order = client.get_order(&order_id);
+    assert_eq!(order.status, OrderStatus::Expired);
+    assert_eq!(usdc_token.balance(&user), total_amount);
+    assert_eq!(usdc_token.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_expire_order_never_picked_up() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    let total_amount: i128 = 1000;
+    let rider_fee: i128 = 100;
+
+    usdc_token.mint(&user, &total_amount);
+
+    // Shorten the pickup deadline
+    client.set_config(&admin, &EscrowConfig { waiting_timeout: 600, pickup_timeout: 300, required_approvals: 1, ..client.get_config() });
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &total_amount, &rider_fee);
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + 200);
+    client.update_order_status(&order_id, &vendor);
+
+    // Pickup deadline counts from when the order became ready
+    env.ledger().set_timestamp(env.ledger().timestamp() + 300);
+    client.expire_order(&order_id);
+
+    let order = client.get_order(&order_id);
+    assert_eq!(order.status, OrderStatus::Expired);
+    assert_eq!(usdc_token.balance(&user), total_amount);
+}
+
+#[test]
+#[should_panic]
+fn test_expire_order_before_deadline() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    let total_amount: i128 = 1000;
+    let rider_fee: i128 = 100;
+
+    usdc_token.mint(&user, &total_amount);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &total_amount, &rider_fee);
+
+    // Deadline has not passed yet (should panic)
+    client.expire_order(&order_id);
+}
+
+#[test]
+fn test_finalize_order_after_confirmation_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    let rider = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
+    let total_amount: i128 = 1000;
+    let rider_fee: i128 = 100;
+
+    usdc_token.mint(&user, &total_amount);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &total_amount, &rider_fee);
+    client.update_order_status(&order_id, &vendor);
+    client.assign_rider(&order_id, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+    client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
+    let (code, salt) = commit_delivery_code(&env, &client, &user, &order_id);
+    client.rider_confirms_delivery(&order_id, &rider, &code, &salt);
+
+    let order = client.get_order(&order_id);
+    assert_eq!(order.delivered_at, Some(env.ledger().timestamp()));
+
+    // Customer never confirms the delivery
+    let config = client.get_config();
+    env.ledger().set_timestamp(env.ledger().timestamp() + config.confirmation_window);
+
+    // Anyone can finalize once the window has closed
+    client.finalize_order(&order_id);
+
+    let order = client.get_order(&order_id);
+    assert_eq!(order.status, OrderStatus::Completed);
+    assert_eq!(usdc_token.balance(&vendor), total_amount - rider_fee);
+    assert_eq!(usdc_token.balance(&rider), rider_fee);
+    assert_eq!(usdc_token.balance(&contract_id), 0);
+}
+
+#[test]
+#[should_panic]
+fn test_finalize_order_within_confirmation_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    let rider = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
+    let total_amount: i128 = 1000;
+    let rider_fee: i128 = 100;
+
+    usdc_token.mint(&user, &total_amount);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &total_amount, &rider_fee);
+    client.update_order_status(&order_id, &vendor);
+    client.assign_rider(&order_id, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+    client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
+    let (code, salt) = commit_delivery_code(&env, &client, &user, &order_id);
+    client.rider_confirms_delivery(&order_id, &rider, &code, &salt);
+
+    // Customer still has time to confirm or dispute (should panic)
+    client.finalize_order(&order_id);
+}
+
+#[test]
+#[should_panic]
+fn test_finalize_disputed_order() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    let rider = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
+    let total_amount: i128 = 1000;
+    let rider_fee: i128 = 100;
+
+    usdc_token.mint(&user, &total_amount);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &total_amount, &rider_fee);
+    client.update_order_status(&order_id, &vendor);
+    client.assign_rider(&order_id, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+    client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
+    let (code, salt) = commit_delivery_code(&env, &client, &user, &order_id);
+    client.rider_confirms_delivery(&order_id, &rider, &code, &salt);
+
+    // Customer disputes within the window
+    client.customer_raise_dispute(&order_id, &user, &String::from_str(&env, "Missing items"), &vec![&env]);
+
+    let config = client.get_config();
+    env.ledger().set_timestamp(env.ledger().timestamp() + config.confirmation_window);
+
+    // Disputed orders are left to the admins (should panic)
+    client.finalize_order(&order_id);
+}
+
+#[test]
+fn test_create_order_with_unsupported_token() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+
+    // Token is never added to the allowlist
+    let usdc_token = create_token(&env, &admin);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    usdc_token.mint(&user, &1000);
+
+    let result = client.try_create_order(&user, &usdc_token.address, &vendor, &1000, &100);
+    assert_eq!(result, Err(Ok(FastBukaError::UnsupportedToken)));
+}
+
+#[test]
+fn test_create_order_above_token_max_amount() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    client.add_accepted_token(&admin, &token_address, &TokenConfig { min_amount: 100, max_amount: 500, enabled: true, approval_threshold: None, appeal_threshold: None });
+    onboard_vendor(&env, &client, &admin, &vendor);
+    usdc_token.mint(&user, &1000);
+
+    // Order exceeds the token's maximum
+    let result = client.try_create_order(&user, &token_address, &vendor, &1000, &100);
+    assert_eq!(result, Err(Ok(FastBukaError::InvalidAmount)));
+}
+
+#[test]
+fn test_paginated_order_queries() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    let rider = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
+    usdc_token.mint(&user1, &5000);
+    usdc_token.mint(&user2, &1000);
+
+    for _ in 0..5 {
+        client.create_order(&user1, &token_address, &vendor, &1000, &100);
+    }
+    let other_id = client.create_order(&user2, &token_address, &vendor, &1000, &100);
+
+    // Pages follow the cursor until it runs out
+    let page = client.get_all_orders(&0, &4);
+    assert_eq!(page.orders.len(), 4);
+    assert_eq!(page.next_cursor, Some(4));
+    let page = client.get_all_orders(&4, &4);
+    assert_eq!(page.orders.len(), 2);
+    assert_eq!(page.next_cursor, None);
+
+    let page = client.get_customer_orders(&user1, &0, &3);
+    assert_eq!(page.orders.len(), 3);
+    assert_eq!(page.next_cursor, Some(3));
+    let page = client.get_customer_orders(&user1, &3, &3);
+    assert_eq!(page.orders.len(), 2);
+    assert_eq!(page.next_cursor, None);
+    assert!(page.orders.iter().all(|order| order.user == user1));
+
+    let page = client.get_customer_orders(&user2, &0, &10);
+    assert_eq!(page.orders.len(), 1);
+    assert_eq!(page.orders.get(0).unwrap().id, other_id);
+
+    assert_eq!(client.get_vendor_orders(&vendor, &0, &10).orders.len(), 6);
+
+    // Status index follows the order through its lifecycle
+    assert_eq!(client.get_orders_by_status(&OrderStatus::Waiting, &0, &10).orders.len(), 6);
+    client.update_order_status(&other_id, &vendor);
+    client.assign_rider(&other_id, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &other_id);
+    client.pickup_order(&other_id, &rider, &pickup_code, &pickup_salt);
+
+    assert_eq!(client.get_orders_by_status(&OrderStatus::Waiting, &0, &10).orders.len(), 5);
+    assert_eq!(client.get_orders_by_status(&OrderStatus::ReadyForPickup, &0, &10).orders.len(), 0);
+    let page = client.get_orders_by_status(&OrderStatus::PickedUp, &0, &10);
+    assert_eq!(page.orders.len(), 1);
+    assert_eq!(page.orders.get(0).unwrap().id, other_id);
+
+    let page = client.get_rider_orders(&rider, &0, &10);
+    assert_eq!(page.orders.len(), 1);
+    assert_eq!(page.orders.get(0).unwrap().id, other_id);
+
+    client.cancel_order(&1, &user1);
+    let page = client.get_orders_by_status(&OrderStatus::Cancelled, &0, &10);
+    assert_eq!(page.orders.get(0).unwrap().id, 1);
+    let page = client.get_orders_by_status(&OrderStatus::Waiting, &0, &10);
+    assert_eq!(page.orders.len(), 4);
+    assert!(page.orders.iter().all(|order| order.status == OrderStatus::Waiting));
+}
+
+#[test]
+fn test_bump_order_extends_ttl() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    usdc_token.mint(&user, &1000);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &1000, &100);
+
+    let order_ttl = || env.as_contract(&contract_id, || env.storage().persistent().get_ttl(&DataKey::Order(order_id)));
+    let instance_ttl = || env.as_contract(&contract_id, || env.storage().instance().get_ttl());
+    assert_eq!(order_ttl(), ORDER_BUMP_AMOUNT);
+    assert_eq!(instance_ttl(), INSTANCE_BUMP_AMOUNT);
+
+    // Hot config lives in instance storage
+    env.as_contract(&contract_id, || {
+        assert!(env.storage().instance().has(&DataKey::Role(Role::SuperAdmin)));
+        assert!(env.storage().instance().has(&DataKey::AcceptedTokens));
+        assert!(env.storage().instance().has(&DataKey::OrderCounter));
+    });
+
+    // Let both drop below their thresholds, then have a keeper bump the order
+    env.ledger().with_mut(|li| li.sequence_number += 2 * DAY_IN_LEDGERS);
+    assert_eq!(order_ttl(), ORDER_BUMP_AMOUNT - 2 * DAY_IN_LEDGERS);
+
+    client.bump_order(&order_id);
+    assert_eq!(order_ttl(), ORDER_BUMP_AMOUNT);
+    assert_eq!(instance_ttl(), INSTANCE_BUMP_AMOUNT);
+
+    // Index entries are kept alive with the order
+    let page = client.get_customer_orders(&user, &0, &10);
+    assert_eq!(page.orders.get(0).unwrap().id, order_id);
+
+    let result = client.try_bump_order(&99);
+    assert_eq!(result, Err(Ok(FastBukaError::OrderNotFound)));
+}
+
+#[test]
+fn test_paused_groups_block_operations_but_not_refunds() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    let rider = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
+    usdc_token.mint(&user, &4000);
+
+    let delivered_id = client.create_order(&user, &token_address, &vendor, &1000, &100);
+    client.update_order_status(&delivered_id, &vendor);
+    client.assign_rider(&delivered_id, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &delivered_id);
+    client.pickup_order(&delivered_id, &rider, &pickup_code, &pickup_salt);
+    let (code, salt) = commit_delivery_code(&env, &client, &user, &delivered_id);
+    client.rider_confirms_delivery(&delivered_id, &rider, &code, &salt);
+
+    let ready_id = client.create_order(&user, &token_address, &vendor, &1000, &100);
+    client.update_order_status(&ready_id, &vendor);
+    client.assign_rider(&ready_id, &vendor, &rider);
+    let waiting_id = client.create_order(&user, &token_address, &vendor, &1000, &100);
+    let rejected_id = client.create_order(&user, &token_address, &vendor, &1000, &100);
+
+    // Only admins can pause
+    let result = client.try_set_paused(&user, &PauseGroup::All, &true);
+    assert_eq!(result, Err(Ok(FastBukaError::MissingRole)));
+
+    // A single group only stops its own operations
+    client.set_paused(&admin, &PauseGroup::NewOrders, &true);
+    assert!(client.is_paused(&PauseGroup::NewOrders));
+    assert!(!client.is_paused(&PauseGroup::Pickups));
+    let result = client.try_create_order(&user, &token_address, &vendor, &1000, &100);
+    assert_eq!(result, Err(Ok(FastBukaError::ContractPaused)));
+
+    // Pausing everything
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &ready_id);
+    client.set_paused(&admin, &PauseGroup::All, &true);
+    let result = client.try_pickup_order(&ready_id, &rider, &pickup_code, &pickup_salt);
+    assert_eq!(result, Err(Ok(FastBukaError::ContractPaused)));
+    let result = client.try_user_confirms_order(&delivered_id, &user);
+    assert_eq!(result, Err(Ok(FastBukaError::ContractPaused)));
+
+    // Every step of getting an order to the customer is stopped
+    let (_, _, code_hash) = handoff_code(&env, &client, &ready_id, 1);
+    assert_eq!(client.try_update_order_status(&waiting_id, &vendor), Err(Ok(FastBukaError::ContractPaused)));
+    assert_eq!(client.try_set_pickup_code(&vendor, &ready_id, &code_hash), Err(Ok(FastBukaError::ContractPaused)));
+    assert_eq!(client.try_set_delivery_code(&user, &ready_id, &code_hash), Err(Ok(FastBukaError::ContractPaused)));
+    assert_eq!(client.try_assign_rider(&waiting_id, &vendor, &rider), Err(Ok(FastBukaError::ContractPaused)));
+    assert_eq!(client.try_accept_assignment(&ready_id, &rider), Err(Ok(FastBukaError::ContractPaused)));
+    assert_eq!(client.try_decline_assignment(&ready_id, &rider), Err(Ok(FastBukaError::ContractPaused)));
+    assert_eq!(client.try_set_rider_online(&rider, &false), Err(Ok(FastBukaError::ContractPaused)));
+    assert_eq!(client.try_rider_confirms_delivery(&delivered_id, &rider, &code, &salt), Err(Ok(FastBukaError::ContractPaused)));
+
+    // So are disputes and onboarding
+    let reason = String::from_str(&env, "Cold food");
+    assert_eq!(client.try_customer_raise_dispute(&delivered_id, &user, &reason, &vec![&env]), Err(Ok(FastBukaError::ContractPaused)));
+    assert_eq!(client.try_rider_raise_dispute(&delivered_id, &rider, &reason, &vec![&env]), Err(Ok(FastBukaError::ContractPaused)));
+    assert_eq!(client.try_respond_to_dispute(&delivered_id, &vendor, &reason, &vec![&env]), Err(Ok(FastBukaError::ContractPaused)));
+    let newcomer = Address::generate(&env);
+    let metadata_uri = String::from_str(&env, "ipfs://newcomer");
+    assert_eq!(client.try_register_vendor(&newcomer, &reason, &newcomer, &metadata_uri), Err(Ok(FastBukaError::ContractPaused)));
+    assert_eq!(client.try_register_rider(&newcomer, &VehicleType::Bicycle, &metadata_uri), Err(Ok(FastBukaError::ContractPaused)));
+
+    // Customers can still get their money back
+    client.cancel_order(&waiting_id, &user);
+    client.reject_order(&rejected_id, &vendor, &RejectionReason::TooBusy);
+    assert_eq!(usdc_token.balance(&user), 2000);
+
+    // Lifting the global pause leaves the single group paused
+    client.set_paused(&admin, &PauseGroup::All, &false);
+    assert!(client.is_paused(&PauseGroup::NewOrders));
+    client.pickup_order(&ready_id, &rider, &pickup_code, &pickup_salt);
+    client.user_confirms_order(&delivered_id, &user);
+    assert_eq!(client.get_order(&delivered_id).status, OrderStatus::Completed);
+
+    client.set_paused(&admin, &PauseGroup::NewOrders, &false);
+    client.create_order(&user, &token_address, &vendor, &1000, &100);
+}
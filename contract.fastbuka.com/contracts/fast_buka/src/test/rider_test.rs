@@ -1,11 +1,11 @@
 #![cfg(test)]
 use crate::{
-    datatypes::OrderStatus,
+    datatypes::{FastBukaError, OrderStatus, RiderStatus, Role, VehicleType},
     FastBukaContract,
     FastBukaContractClient
 };
-use soroban_sdk::{testutils::Address as _, Address, Env, String};
-use super::common::create_token;
+use soroban_sdk::{testutils::{Address as _, Ledger}, vec, Address, Bytes, Env, String};
+use super::common::{allow_token, commit_delivery_code, commit_pickup_code, create_token, handoff_code, onboard_rider, onboard_vendor};
 extern crate std;
 
 #[test]
@@ -32,6 +32,9 @@ fn test_rider_pickup_flow() {
     // Create and setup token
     let usdc_token = create_token(&env, &admin);
     let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
     let total_amount: i128 = 1000;
     let rider_fee: i128 = 100;
     usdc_token.mint(&user, &total_amount);
@@ -41,22 +44,22 @@ fn test_rider_pickup_flow() {
     std::println!("\nOrder created with ID: {}", order_id);
 
     // Update order status with vendor
-    let confirmation = client.update_order_status(&order_id, &vendor);
-    std::println!("Confirmation code: {:?}", confirmation);
+    client.update_order_status(&order_id, &vendor);
 
     
 
     // Test pickup_order
-    client.pickup_order(&order_id, &rider);
+    client.assign_rider(&order_id, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+    client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
     let order = client.get_order(&order_id);
-    let rider_confirm_no = client.get_confirmation_number_rider(&order_id);
-    std::println!("rider_confirm_no: {}", rider_confirm_no);
 
     assert_eq!(order.status, OrderStatus::PickedUp);
-    assert_eq!(order.rider, Some(rider));
+    assert_eq!(order.rider, Some(rider.clone()));
     
     // Test rider_confirms_delivery
-    client.rider_confirms_delivery(&order_id);
+    let (code, salt) = commit_delivery_code(&env, &client, &user, &order_id);
+    client.rider_confirms_delivery(&order_id, &rider, &code, &salt);
     let order = client.get_order(&order_id);
     assert_eq!(order.status, OrderStatus::Delivered);
 }
@@ -81,6 +84,9 @@ fn test_rider_dispute() {
     // Create and setup token
     let usdc_token = create_token(&env, &admin);
     let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
     let total_amount: i128 = 1000;
     let rider_fee: i128 = 100;
     usdc_token.mint(&user, &total_amount);
@@ -89,24 +95,24 @@ fn test_rider_dispute() {
     // Create and process order through delivery
     let order_id = client.create_order(&user, &token_address, &vendor, &total_amount, &rider_fee);
     // Update order status with vendor
-    let confirmation = client.update_order_status(&order_id, &vendor);
-    std::println!("Confirmation code: {:?}", confirmation);
+    client.update_order_status(&order_id, &vendor);
 
     // Test pickup_order
-    client.pickup_order(&order_id, &rider);
+    client.assign_rider(&order_id, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+    client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
     let order = client.get_order(&order_id);
-    let rider_confirm_no = client.get_confirmation_number_rider(&order_id);
-    std::println!("rider_confirm_no: {}", rider_confirm_no);
 
     assert_eq!(order.status, OrderStatus::PickedUp);
     assert_eq!(order.rider, Some(rider.clone()));
     
     // Test rider_confirms_delivery
-    client.rider_confirms_delivery(&order_id);
+    let (code, salt) = commit_delivery_code(&env, &client, &user, &order_id);
+    client.rider_confirms_delivery(&order_id, &rider, &code, &salt);
     let order = client.get_order(&order_id);
     assert_eq!(order.status, OrderStatus::Delivered);
 
-    client.rider_raise_dispute(&order_id, &rider, &reason);
+    client.rider_raise_dispute(&order_id, &rider, &reason, &vec![&env]);
     
     let order = client.get_order(&order_id);
     assert_eq!(order.status, OrderStatus::Disputed);
@@ -132,6 +138,9 @@ fn test_pickup_order_not_ready() {
     // Create and setup token
     let usdc_token = create_token(&env, &admin);
     let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
     let total_amount: i128 = 1000;
     let rider_fee: i128 = 100;
     usdc_token.mint(&user, &total_amount);
@@ -142,11 +151,11 @@ fn test_pickup_order_not_ready() {
     
 
     // Test pickup_order
-    client.pickup_order(&order_id, &rider);
+    client.assign_rider(&order_id, &vendor, &rider);
+    client.pickup_order(&order_id, &rider, &123456, &Bytes::from_array(&env, &[0; 32]));
 }
 
 #[test]
-#[should_panic]
 fn test_dispute_before_delivery() {
     let env = Env::default();
     env.mock_all_auths();
@@ -167,6 +176,9 @@ fn test_dispute_before_delivery() {
     // Create and setup token
     let usdc_token = create_token(&env, &admin);
     let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
     let total_amount: i128 = 1000;
     let rider_fee: i128 = 100;
     usdc_token.mint(&user, &total_amount);
@@ -175,20 +187,403 @@ fn test_dispute_before_delivery() {
     // Create and process order through delivery
     let order_id = client.create_order(&user, &token_address, &vendor, &total_amount, &rider_fee);
     // Update order status with vendor
-    let confirmation = client.update_order_status(&order_id, &vendor);
-    std::println!("Confirmation code: {:?}", confirmation);
+    client.update_order_status(&order_id, &vendor);
+
+    // Nothing to dispute before the rider has the food
+    client.assign_rider(&order_id, &vendor, &rider);
+    let result = client.try_customer_raise_dispute(&order_id, &user, &reason, &vec![&env]);
+    assert_eq!(result, Err(Ok(FastBukaError::OrderNotDelivered)));
 
     // Test pickup_order
-    client.pickup_order(&order_id, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+    client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
     let order = client.get_order(&order_id);
-    let rider_confirm_no = client.get_confirmation_number_rider(&order_id);
-    std::println!("rider_confirm_no: {}", rider_confirm_no);
 
     assert_eq!(order.status, OrderStatus::PickedUp);
     assert_eq!(order.rider, Some(rider.clone()));
-    
 
-    client.rider_raise_dispute(&order_id, &rider, &reason);
+    // A rider left waiting on the customer's code can dispute the picked-up order
+    client.rider_raise_dispute(&order_id, &rider, &reason, &vec![&env]);
+    assert_eq!(client.get_order(&order_id).status, OrderStatus::Disputed);
+}
+
+
+
+#[test]
+fn test_rider_registration_and_status() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let rider = Address::generate(&env);
+    let metadata_uri = String::from_str(&env, "ipfs://rider-profile");
+
+    client.register_rider(&rider, &VehicleType::Bicycle, &metadata_uri);
+
+    // New riders are pending and offline
+    let profile = client.get_rider(&rider);
+    assert_eq!(profile.vehicle_type, VehicleType::Bicycle);
+    assert_eq!(profile.status, RiderStatus::Pending);
+    assert!(!profile.online);
+
+    client.approve_rider(&admin, &rider);
+    client.set_rider_online(&rider, &true);
+
+    let profile = client.get_rider(&rider);
+    assert_eq!(profile.status, RiderStatus::Approved);
+    assert!(profile.online);
+
+    client.suspend_rider(&admin, &rider);
+    assert_eq!(client.get_rider(&rider).status, RiderStatus::Suspended);
 }
 
+#[test]
+fn test_pickup_rejects_inactive_riders() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    let rider = Address::generate(&env);
+    let unregistered_rider = Address::generate(&env);
+    let pending_rider = Address::generate(&env);
 
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
+    usdc_token.mint(&user, &1000);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &1000, &100);
+    client.update_order_status(&order_id, &vendor);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+    client.assign_rider(&order_id, &vendor, &rider);
+
+    // Unregistered rider
+    let result = client.try_pickup_order(&order_id, &unregistered_rider, &pickup_code, &pickup_salt);
+    assert_eq!(result, Err(Ok(FastBukaError::RiderNotRegistered)));
+
+    // Registered but not yet approved
+    client.register_rider(&pending_rider, &VehicleType::Car, &String::from_str(&env, ""));
+    let result = client.try_pickup_order(&order_id, &pending_rider, &pickup_code, &pickup_salt);
+    assert_eq!(result, Err(Ok(FastBukaError::RiderNotApproved)));
+
+    // Approved but offline
+    client.set_rider_online(&rider, &false);
+    let result = client.try_pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
+    assert_eq!(result, Err(Ok(FastBukaError::RiderOffline)));
+
+    // Suspended
+    client.set_rider_online(&rider, &true);
+    client.suspend_rider(&admin, &rider);
+    let result = client.try_pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
+    assert_eq!(result, Err(Ok(FastBukaError::RiderSuspended)));
+
+    // Reinstated riders can pick up again
+    client.approve_rider(&admin, &rider);
+    client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
+    assert_eq!(client.get_order(&order_id).status, OrderStatus::PickedUp);
+}
+
+#[test]
+fn test_only_assigned_rider_can_pickup() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    let rider = Address::generate(&env);
+    let other_rider = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
+    onboard_rider(&env, &client, &admin, &other_rider);
+    usdc_token.mint(&user, &1000);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &1000, &100);
+    client.update_order_status(&order_id, &vendor);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+
+    // Nobody is assigned yet
+    let result = client.try_pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
+    assert_eq!(result, Err(Ok(FastBukaError::RiderNotAssigned)));
+
+    client.assign_rider(&order_id, &vendor, &rider);
+    client.accept_assignment(&order_id, &rider);
+    assert!(client.get_order(&order_id).assignment_accepted);
+
+    // Another rider cannot front-run the assigned one
+    let result = client.try_pickup_order(&order_id, &other_rider, &pickup_code, &pickup_salt);
+    assert_eq!(result, Err(Ok(FastBukaError::RiderNotAssigned)));
+
+    // Vendor cannot replace a rider who accepted
+    let result = client.try_assign_rider(&order_id, &vendor, &other_rider);
+    assert_eq!(result, Err(Ok(FastBukaError::RiderAlreadyAssigned)));
+
+    // Neither can anyone without the dispatcher role, but a dispatcher can
+    let dispatcher = Address::generate(&env);
+    let result = client.try_assign_rider(&order_id, &dispatcher, &other_rider);
+    assert_eq!(result, Err(Ok(FastBukaError::UnauthorizedAccess)));
+    client.grant_role(&admin, &dispatcher, &Role::Dispatcher);
+    client.assign_rider(&order_id, &dispatcher, &other_rider);
+
+    let result = client.try_pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
+    assert_eq!(result, Err(Ok(FastBukaError::RiderNotAssigned)));
+    client.pickup_order(&order_id, &other_rider, &pickup_code, &pickup_salt);
+    assert_eq!(client.get_order(&order_id).rider, Some(other_rider));
+}
+
+#[test]
+fn test_reassign_rider_after_decline_or_timeout() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    let rider1 = Address::generate(&env);
+    let rider2 = Address::generate(&env);
+    let rider3 = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider1);
+    onboard_rider(&env, &client, &admin, &rider2);
+    onboard_rider(&env, &client, &admin, &rider3);
+    usdc_token.mint(&user, &1000);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &1000, &100);
+    client.update_order_status(&order_id, &vendor);
+
+    // First rider declines
+    client.assign_rider(&order_id, &vendor, &rider1);
+    client.decline_assignment(&order_id, &rider1);
+    assert_eq!(client.get_order(&order_id).assigned_rider, None);
+
+    // Second rider never responds
+    client.assign_rider(&order_id, &vendor, &rider2);
+    let result = client.try_assign_rider(&order_id, &vendor, &rider3);
+    assert_eq!(result, Err(Ok(FastBukaError::RiderAlreadyAssigned)));
+
+    let config = client.get_config();
+    env.ledger().set_timestamp(env.ledger().timestamp() + config.assignment_timeout);
+
+    // Lapsed assignment can no longer be accepted and is reassigned
+    let result = client.try_accept_assignment(&order_id, &rider2);
+    assert_eq!(result, Err(Ok(FastBukaError::DeadlinePassed)));
+    client.assign_rider(&order_id, &vendor, &rider3);
+
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+
+    client.pickup_order(&order_id, &rider3, &pickup_code, &pickup_salt);
+    assert_eq!(client.get_order(&order_id).rider, Some(rider3));
+}
+
+#[test]
+fn test_delivery_code_lockout() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    let rider = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
+    usdc_token.mint(&user, &1000);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &1000, &100);
+    client.update_order_status(&order_id, &vendor);
+    client.assign_rider(&order_id, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+    client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
+
+    let (code, salt) = commit_delivery_code(&env, &client, &user, &order_id);
+    let wrong_code = if code == 9999 { 1000 } else { code + 1 };
+
+    // Wrong codes are counted but do not deliver the order
+    let config = client.get_config();
+    for attempt in 1..config.max_code_attempts {
+        assert!(!client.rider_confirms_delivery(&order_id, &rider, &wrong_code, &salt));
+        assert_eq!(client.get_order(&order_id).failed_delivery_attempts, attempt);
+    }
+
+    // Last allowed attempt locks the order
+    assert!(!client.rider_confirms_delivery(&order_id, &rider, &wrong_code, &salt));
+    let order = client.get_order(&order_id);
+    assert_eq!(order.status, OrderStatus::PickedUp);
+    assert_eq!(order.delivery_locked_until, env.ledger().timestamp() + config.code_lockout);
+
+    // Even the right code is refused while locked
+    let result = client.try_rider_confirms_delivery(&order_id, &rider, &code, &salt);
+    assert_eq!(result, Err(Ok(FastBukaError::CodeAttemptsExceeded)));
+
+    // Lock expires
+    env.ledger().set_timestamp(env.ledger().timestamp() + config.code_lockout);
+    assert!(client.rider_confirms_delivery(&order_id, &rider, &code, &salt));
+    assert_eq!(client.get_order(&order_id).status, OrderStatus::Delivered);
+}
+
+#[test]
+fn test_only_order_rider_can_confirm_delivery() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    let rider = Address::generate(&env);
+    let other_rider = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
+    usdc_token.mint(&user, &1000);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &1000, &100);
+    client.update_order_status(&order_id, &vendor);
+    client.assign_rider(&order_id, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+    client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
+
+    let (code, salt) = commit_delivery_code(&env, &client, &user, &order_id);
+    let result = client.try_rider_confirms_delivery(&order_id, &other_rider, &code, &salt);
+    assert_eq!(result, Err(Ok(FastBukaError::UnauthorizedAccess)));
+}
+
+#[test]
+fn test_pickup_requires_vendor_code() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    let rider = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
+    usdc_token.mint(&user, &1000);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &1000, &100);
+    client.update_order_status(&order_id, &vendor);
+    client.assign_rider(&order_id, &vendor, &rider);
+
+    // No pickup code committed yet
+    let (pickup_code, pickup_salt, pickup_hash) = handoff_code(&env, &client, &order_id, 0);
+    let result = client.try_pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
+    assert_eq!(result, Err(Ok(FastBukaError::InvalidConfirmationNumber)));
+
+    // Only the order's vendor can set the pickup code, and only the customer the delivery code
+    let result = client.try_set_pickup_code(&rider, &order_id, &pickup_hash);
+    assert_eq!(result, Err(Ok(FastBukaError::UnauthorizedAccess)));
+    let (_, _, code_hash) = handoff_code(&env, &client, &order_id, 1);
+    let result = client.try_set_delivery_code(&rider, &order_id, &code_hash);
+    assert_eq!(result, Err(Ok(FastBukaError::UnauthorizedAccess)));
+
+    client.set_pickup_code(&vendor, &order_id, &pickup_hash);
+    assert!(!client.pickup_order(&order_id, &rider, &(pickup_code + 1), &pickup_salt));
+    assert_eq!(client.get_order(&order_id).status, OrderStatus::ReadyForPickup);
+
+    assert!(client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt));
+    assert_eq!(client.get_order(&order_id).status, OrderStatus::PickedUp);
+
+    // Once picked up the vendor leg is done
+    let result = client.try_set_pickup_code(&vendor, &order_id, &pickup_hash);
+    assert_eq!(result, Err(Ok(FastBukaError::InvalidStatus)));
+}
+
+#[test]
+fn test_pickup_code_lockout() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    let rider = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    onboard_rider(&env, &client, &admin, &rider);
+    usdc_token.mint(&user, &1000);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &1000, &100);
+    client.update_order_status(&order_id, &vendor);
+    client.assign_rider(&order_id, &vendor, &rider);
+    let (pickup_code, pickup_salt) = commit_pickup_code(&env, &client, &vendor, &order_id);
+    let wrong_code = if pickup_code == 999_999 { 100_000 } else { pickup_code + 1 };
+
+    // Wrong codes are counted but do not release the food
+    let config = client.get_config();
+    for attempt in 1..config.max_code_attempts {
+        assert!(!client.pickup_order(&order_id, &rider, &wrong_code, &pickup_salt));
+        assert_eq!(client.get_order(&order_id).failed_pickup_attempts, attempt);
+    }
+
+    // Last allowed attempt locks the order
+    assert!(!client.pickup_order(&order_id, &rider, &wrong_code, &pickup_salt));
+    let order = client.get_order(&order_id);
+    assert_eq!(order.status, OrderStatus::ReadyForPickup);
+    assert_eq!(order.pickup_locked_until, env.ledger().timestamp() + config.code_lockout);
+
+    // Even the right code is refused while locked
+    let result = client.try_pickup_order(&order_id, &rider, &pickup_code, &pickup_salt);
+    assert_eq!(result, Err(Ok(FastBukaError::CodeAttemptsExceeded)));
+
+    // Lock expires
+    env.ledger().set_timestamp(env.ledger().timestamp() + config.code_lockout);
+    assert!(client.pickup_order(&order_id, &rider, &pickup_code, &pickup_salt));
+    assert_eq!(client.get_order(&order_id).status, OrderStatus::PickedUp);
+}
@@ -3,10 +3,10 @@
 use crate::{
     FastBukaContract,
     FastBukaContractClient,
-    datatypes::OrderStatus
+    datatypes::{FastBukaError, OrderStatus, RejectionReason, VendorStatus, MAX_PAGE_LIMIT}
 };
-use soroban_sdk::{testutils::Address as _, Address, Env};
-use super::common::{create_token};
+use soroban_sdk::{testutils::{Address as _, Ledger}, vec, Address, Env, String};
+use super::common::{allow_token, create_token, onboard_vendor};
 extern crate std;
 
 
@@ -27,6 +27,9 @@ fn test_get_vendor_pending_orders() {
 
     let usdc_token = create_token(&env, &admin);
     let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor1);
+    onboard_vendor(&env, &client, &admin, &vendor2);
 
     usdc_token.mint(&user1, &2000);
 
@@ -39,16 +42,16 @@ fn test_get_vendor_pending_orders() {
 
 
     // Get vendor1's pending orders
-    let vendor_orders = client.get_vendor_pending_orders(&vendor1);
-    assert_eq!(vendor_orders.len(), 3, "Expected 2 orders for vendor1");
+    let pending = vec![&env, OrderStatus::Waiting, OrderStatus::ReadyForPickup];
+    let vendor_orders = client.get_vendor_pending_orders(&vendor1, &pending, &0, &u64::MAX, &0, &MAX_PAGE_LIMIT).orders;
+    assert_eq!(vendor_orders.len(), 3, "Expected 3 orders for vendor1");
 
-    let vendor_orders = client.get_vendor_pending_orders(&vendor2);
-    assert_eq!(vendor_orders.len(), 1, "Expected 2 orders for vendor2");
+    let vendor_orders = client.get_vendor_pending_orders(&vendor2, &pending, &0, &u64::MAX, &0, &MAX_PAGE_LIMIT).orders;
+    assert_eq!(vendor_orders.len(), 1, "Expected 1 order for vendor2");
 
 }
 
 #[test]
-#[should_panic]
 fn test_get_vendor_no_found_orders() {
     let env = Env::default();
     env.mock_all_auths();
@@ -65,6 +68,9 @@ fn test_get_vendor_no_found_orders() {
 
     let usdc_token = create_token(&env, &admin);
     let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor1);
+    onboard_vendor(&env, &client, &admin, &vendor2);
 
     usdc_token.mint(&user1, &2000);
 
@@ -72,8 +78,108 @@ fn test_get_vendor_no_found_orders() {
     let _order1_id = client.create_order(&user1, &token_address, &vendor1, &500, &50);
     
 
-    // Get vendor1's pending orders
-    let _vendor_orders = client.get_vendor_pending_orders(&vendor2);
+    // Vendor2 has nothing to show
+    let pending = vec![&env, OrderStatus::Waiting, OrderStatus::ReadyForPickup];
+    let vendor_orders = client.get_vendor_pending_orders(&vendor2, &pending, &0, &u64::MAX, &0, &MAX_PAGE_LIMIT).orders;
+    assert!(vendor_orders.is_empty());
+}
+
+
+#[test]
+fn test_vendor_pending_orders_filters_status_and_time() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    usdc_token.mint(&user, &4000);
+
+    env.ledger().set_timestamp(1_000);
+    let ready_id = client.create_order(&user, &token_address, &vendor, &1000, &100);
+    let cancelled_id = client.create_order(&user, &token_address, &vendor, &1000, &100);
+    env.ledger().set_timestamp(2_000);
+    let waiting_id = client.create_order(&user, &token_address, &vendor, &1000, &100);
+    env.ledger().set_timestamp(3_000);
+    let late_id = client.create_order(&user, &token_address, &vendor, &1000, &100);
+
+    client.update_order_status(&ready_id, &vendor);
+    client.cancel_order(&cancelled_id, &user);
+
+    // Finished orders are left out, and orders come status by status
+    let pending = vec![&env, OrderStatus::Waiting, OrderStatus::ReadyForPickup];
+    let orders = client.get_vendor_pending_orders(&vendor, &pending, &0, &u64::MAX, &0, &MAX_PAGE_LIMIT).orders;
+    let ids: std::vec::Vec<u128> = orders.iter().map(|order| order.id).collect();
+    assert_eq!(ids.len(), 3);
+    assert!(ids.contains(&waiting_id) && ids.contains(&late_id));
+    assert_eq!(ids[2], ready_id);
+
+    // Created-at range is inclusive
+    let orders = client.get_vendor_pending_orders(&vendor, &pending, &1_000, &2_000, &0, &MAX_PAGE_LIMIT).orders;
+    let ids: std::vec::Vec<u128> = orders.iter().map(|order| order.id).collect();
+    assert_eq!(ids, std::vec![waiting_id, ready_id]);
+
+    let cancelled = vec![&env, OrderStatus::Cancelled];
+    let orders = client.get_vendor_pending_orders(&vendor, &cancelled, &0, &u64::MAX, &0, &MAX_PAGE_LIMIT).orders;
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders.get(0).unwrap().id, cancelled_id);
+
+    let orders = client.get_vendor_pending_orders(&vendor, &pending, &3_001, &u64::MAX, &0, &MAX_PAGE_LIMIT).orders;
+    assert!(orders.is_empty());
+
+    // An inverted range is refused
+    let result = client.try_get_vendor_pending_orders(&vendor, &pending, &2_000, &1_000, &0, &MAX_PAGE_LIMIT);
+    assert_eq!(result.err(), Some(Ok(FastBukaError::InvalidTimeRange)));
+}
+
+#[test]
+fn test_vendor_pending_orders_pages_across_statuses() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    usdc_token.mint(&user, &5000);
+
+    let mut created = std::vec::Vec::new();
+    for _ in 0..5 {
+        created.push(client.create_order(&user, &token_address, &vendor, &1000, &100));
+    }
+    client.update_order_status(&created[0], &vendor);
+    client.update_order_status(&created[1], &vendor);
+
+    // Listing a status twice does not repeat its orders
+    let pending = vec![&env, OrderStatus::Waiting, OrderStatus::ReadyForPickup, OrderStatus::Waiting];
+    let mut seen = std::vec::Vec::new();
+    let mut cursor = Some(0);
+    while let Some(start) = cursor {
+        let page = client.get_vendor_pending_orders(&vendor, &pending, &0, &u64::MAX, &start, &2);
+        assert!(page.orders.len() <= 2);
+        seen.extend(page.orders.iter().map(|order| order.id));
+        cursor = page.next_cursor;
+    }
+
+    seen.sort();
+    assert_eq!(seen, created);
 }
 
 #[test]
@@ -93,6 +199,8 @@ fn test_update_order_status_by_vendor() {
     // Setup token and amounts
     let usdc_token = create_token(&env, &admin);
     let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
     let total_amount: i128 = 1000;
     let rider_fee: i128 = 100;
     
@@ -107,13 +215,12 @@ fn test_update_order_status_by_vendor() {
     std::println!("Created order with ID: {}", order_id);
     
     // Update order status with vendor
-    let confirmation = client.update_order_status(&order_id, &vendor);
-    std::println!("Confirmation code: {:?}", confirmation);
+    client.update_order_status(&order_id, &vendor);
     let updated_order = client.get_order(&order_id);
     
-    // Verify status updated and confirmation number generated
+    // Verify status updated; codes are only set by the party handing them over
     assert_eq!(updated_order.status, OrderStatus::ReadyForPickup);
-    assert!(updated_order.confirmation_number.is_some());
+    assert!(updated_order.pickup_code_hash.is_none());
 }
 
 #[test]
@@ -134,6 +241,8 @@ fn test_update_order_status_by_wrong_vendor() {
     
     let usdc_token = create_token(&env, &admin);
     let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
     let total_amount: i128 = 1000;
     let rider_fee: i128 = 100;
     
@@ -144,3 +253,184 @@ fn test_update_order_status_by_wrong_vendor() {
     // This should panic when wrong vendor tries to update
     client.update_order_status(&order_id, &wrong_vendor);
 }
+
+#[test]
+fn test_vendor_reject_order() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    let total_amount: i128 = 1000;
+    let rider_fee: i128 = 100;
+
+    usdc_token.mint(&user, &total_amount);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &total_amount, &rider_fee);
+
+    assert_eq!(client.get_rejection_reason(&order_id), None);
+
+    // Vendor declines the order with a free text reason
+    let reason = RejectionReason::Other(String::from_str(&env, "Kitchen maintenance"));
+    client.reject_order(&order_id, &vendor, &reason);
+
+    // Verify order is cancelled, reason recorded and customer refunded
+    let order = client.get_order(&order_id);
+    assert_eq!(order.status, OrderStatus::Cancelled);
+    assert_eq!(client.get_rejection_reason(&order_id), Some(reason));
+    assert_eq!(usdc_token.balance(&user), total_amount);
+    assert_eq!(usdc_token.balance(&contract_id), 0);
+}
+
+#[test]
+#[should_panic]
+fn test_reject_order_by_wrong_vendor() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    let wrong_vendor = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    let total_amount: i128 = 1000;
+    let rider_fee: i128 = 100;
+
+    usdc_token.mint(&user, &total_amount);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &total_amount, &rider_fee);
+
+    // This should panic when another vendor tries to reject
+    client.reject_order(&order_id, &wrong_vendor, &RejectionReason::OutOfStock);
+}
+
+#[test]
+#[should_panic]
+fn test_reject_order_after_ready_for_pickup() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    let total_amount: i128 = 1000;
+    let rider_fee: i128 = 100;
+
+    usdc_token.mint(&user, &total_amount);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &total_amount, &rider_fee);
+    client.update_order_status(&order_id, &vendor);
+
+    // Order was already accepted (should panic)
+    client.reject_order(&order_id, &vendor, &RejectionReason::TooBusy);
+}
+
+#[test]
+fn test_vendor_registration_and_approval() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let vendor = Address::generate(&env);
+    let payout_address = Address::generate(&env);
+    let name = String::from_str(&env, "Mama Put");
+    let metadata_uri = String::from_str(&env, "ipfs://mama-put");
+
+    client.register_vendor(&vendor, &name, &payout_address, &metadata_uri);
+
+    // New vendors wait for approval
+    let profile = client.get_vendor(&vendor);
+    assert_eq!(profile.name, name);
+    assert_eq!(profile.payout_address, payout_address);
+    assert_eq!(profile.status, VendorStatus::Pending);
+
+    client.approve_vendor(&admin, &vendor);
+    assert_eq!(client.get_vendor(&vendor).status, VendorStatus::Approved);
+
+    client.suspend_vendor(&admin, &vendor);
+    assert_eq!(client.get_vendor(&vendor).status, VendorStatus::Suspended);
+}
+
+#[test]
+#[should_panic]
+fn test_create_order_for_unapproved_vendor() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    usdc_token.mint(&user, &1000);
+
+    // Vendor registers but is never approved
+    client.register_vendor(&vendor, &String::from_str(&env, "Pending Kitchen"), &vendor, &String::from_str(&env, ""));
+
+    // This should panic since the vendor is still pending
+    client.create_order(&user, &token_address, &vendor, &1000, &100);
+}
+
+#[test]
+#[should_panic]
+fn test_suspended_vendor_cannot_update_order() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+
+    let contract_id = env.register(FastBukaContract, (admin.clone(),));
+    let client = FastBukaContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let vendor = Address::generate(&env);
+
+    let usdc_token = create_token(&env, &admin);
+    let token_address = usdc_token.address.clone();
+    allow_token(&client, &admin, &token_address);
+    onboard_vendor(&env, &client, &admin, &vendor);
+    usdc_token.mint(&user, &1000);
+
+    let order_id = client.create_order(&user, &token_address, &vendor, &1000, &100);
+    client.suspend_vendor(&admin, &vendor);
+
+    // This should panic since the vendor is suspended
+    client.update_order_status(&order_id, &vendor);
+}